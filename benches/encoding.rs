@@ -0,0 +1,40 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use cyberdoc_generator::analysis::Analysis;
+use cyberdoc_generator::fixture::synthetic_pool;
+use cyberdoc_generator::usage::UsageIndex;
+use cyberdoc_generator::{encode_definition, is_documented};
+
+const SIZES: &[usize] = &[100, 1000];
+
+fn analysis(c: &mut Criterion) {
+    let mut group = c.benchmark_group("analysis");
+    for &size in SIZES {
+        let pool = synthetic_pool(size);
+        group.bench_with_input(BenchmarkId::new("usages", size), &pool, |b, pool| {
+            b.iter(|| UsageIndex::build(black_box(pool)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("full", size), &pool, |b, pool| {
+            b.iter(|| Analysis::build(black_box(pool)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn encoding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encoding");
+    for &size in SIZES {
+        let pool = synthetic_pool(size);
+        let analysis = Analysis::build(&pool).unwrap();
+        group.bench_with_input(BenchmarkId::new("roots", size), &pool, |b, pool| {
+            b.iter(|| {
+                for (_, def) in pool.roots().filter(|(_, def)| is_documented(def)) {
+                    black_box(encode_definition(def, pool, &analysis).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, analysis, encoding);
+criterion_main!(benches);
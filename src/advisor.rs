@@ -0,0 +1,186 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::Path;
+
+use redscript::bundle::{ConstantPool, PoolIndex};
+use redscript::bytecode::Instr;
+use redscript::definition::{AnyDefinition, Definition};
+use serde_json::Value;
+
+use crate::render::unmangled;
+
+/// Prefix of symbols that are only known by their member name, e.g. methods called virtually.
+const ANY_OWNER: &str = "*";
+
+/// Collects the symbols referenced by the code of a bundle as `Name` for root definitions and
+/// `Owner::member` for members, optionally limited to functions defined under a source path.
+pub fn referenced_symbols(pool: &ConstantPool, source_prefix: Option<&Path>) -> anyhow::Result<BTreeSet<String>> {
+    let mut symbols = BTreeSet::new();
+    for (_, def) in pool.definitions() {
+        let AnyDefinition::Function(fun) = &def.value else {
+            continue;
+        };
+        if let Some(prefix) = source_prefix {
+            let file = fun
+                .source
+                .as_ref()
+                .map(|source| pool.definition(source.file))
+                .transpose()?;
+            match file.map(|file| &file.value) {
+                Some(AnyDefinition::SourceFile(file)) if file.path.starts_with(prefix) => {}
+                _ => continue,
+            }
+        }
+        for instr in &fun.code.0 {
+            let symbol = match instr {
+                Instr::InvokeStatic(_, _, callee, ..) => symbol_name(callee.cast(), pool)?,
+                Instr::InvokeVirtual(_, _, name, ..) => format!("{}::{}", ANY_OWNER, pool.names.get(*name)?),
+                Instr::New(class) | Instr::Construct(_, class) => symbol_name(class.cast(), pool)?,
+                Instr::ObjectField(field) | Instr::StructField(field) => symbol_name(field.cast(), pool)?,
+                Instr::EnumConst(_, member) => symbol_name(member.cast(), pool)?,
+                _ => continue,
+            };
+            symbols.insert(symbol);
+        }
+    }
+    Ok(symbols)
+}
+
+/// Reads a list of symbols in the same format, one per line.
+pub fn read_symbols(path: &Path) -> anyhow::Result<BTreeSet<String>> {
+    Ok(std::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_owned)
+        .collect())
+}
+
+/// Finds the symbols affected by the changes in a `changes.json` document, ordered so that the
+/// ones that break the mod come first.
+pub fn advise(changes: &Value, symbols: &BTreeSet<String>) -> Vec<Advice> {
+    let removed_roots: BTreeSet<&str> = changes["removed"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|sym| sym["name"].as_str())
+        .map(unmangled)
+        .collect();
+    let owners = changes["members"].as_object();
+
+    let mut advice = vec![];
+    for symbol in symbols {
+        let Some((owner, member)) = symbol.split_once("::") else {
+            if removed_roots.contains(unmangled(symbol)) {
+                advice.push(Advice::new(Priority::Removed, symbol, "removed".to_owned()));
+            }
+            continue;
+        };
+        if owner != ANY_OWNER && removed_roots.contains(unmangled(owner)) {
+            advice.push(Advice::new(
+                Priority::Removed,
+                symbol,
+                format!("owner {} was removed", unmangled(owner)),
+            ));
+            continue;
+        }
+        let candidates = owners
+            .into_iter()
+            .flatten()
+            .filter(|(name, _)| owner == ANY_OWNER || unmangled(name) == unmangled(owner));
+        for (name, owner_changes) in candidates {
+            let matches = |entry: &&Value| {
+                entry
+                    .as_str()
+                    .or_else(|| entry["name"].as_str())
+                    .is_some_and(|entry| unmangled(entry) == unmangled(member))
+            };
+            if owner_changes["removed"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|e| matches(&e))
+            {
+                advice.push(Advice::new(
+                    Priority::Removed,
+                    symbol,
+                    format!("removed from {}", unmangled(name)),
+                ));
+            }
+            for change in owner_changes["changed"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(matches)
+            {
+                advice.push(Advice::new(
+                    Priority::Changed,
+                    symbol,
+                    format!(
+                        "changed in {}: `{}` → `{}`",
+                        unmangled(name),
+                        change["before"].as_str().unwrap_or_default(),
+                        change["after"].as_str().unwrap_or_default()
+                    ),
+                ));
+            }
+        }
+    }
+    advice.sort_by(|a, b| (a.priority, &a.symbol).cmp(&(b.priority, &b.symbol)));
+    advice
+}
+
+pub fn render_markdown(advice: &[Advice], symbol_count: usize) -> anyhow::Result<String> {
+    let mut out = String::from("# Migration report\n");
+    writeln!(
+        out,
+        "\nChecked {} referenced symbols, {} affected.",
+        symbol_count,
+        advice.len()
+    )?;
+    for (priority, title) in [
+        (Priority::Removed, "Must fix: removed API"),
+        (Priority::Changed, "Review: changed signatures"),
+    ] {
+        let mut entries = advice.iter().filter(|entry| entry.priority == priority).peekable();
+        if entries.peek().is_some() {
+            writeln!(out, "\n## {}\n", title)?;
+            for entry in entries {
+                writeln!(out, "- `{}`: {}", entry.symbol, entry.message)?;
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Removed,
+    Changed,
+}
+
+pub struct Advice {
+    priority: Priority,
+    symbol: String,
+    message: String,
+}
+
+impl Advice {
+    fn new(priority: Priority, symbol: &str, message: String) -> Self {
+        Self {
+            priority,
+            symbol: symbol.to_owned(),
+            message,
+        }
+    }
+}
+
+fn symbol_name(idx: PoolIndex<Definition>, pool: &ConstantPool) -> anyhow::Result<String> {
+    let def = pool.definition(idx)?;
+    let name = pool.names.get(def.name)?;
+    if def.parent == PoolIndex::UNDEFINED {
+        Ok(name.to_string())
+    } else {
+        Ok(format!("{}::{}", pool.def_name(def.parent)?, name))
+    }
+}
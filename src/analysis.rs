@@ -0,0 +1,320 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use redscript::bundle::{CName, ConstantPool, PoolIndex};
+use redscript::bytecode::{Instr, Offset};
+use redscript::definition::{AnyDefinition, Definition, Field, Function, Type};
+use serde_json::{json, Value};
+
+use crate::diff::read_json;
+use crate::localization;
+use crate::usage::{type_lookup, UsageIndex};
+
+/// Pool-wide analysis results consulted while encoding documents.
+pub struct Analysis {
+    pub usages: UsageIndex,
+    /// Localization keys referenced from bytecode with the functions using them.
+    pub localization: Value,
+    implementations: HashMap<PoolIndex<Definition>, Vec<PoolIndex<Definition>>>,
+    types: HashMap<PoolIndex<CName>, PoolIndex<Definition>>,
+    max_type_depth: usize,
+}
+
+impl Analysis {
+    pub const DEFAULT_MAX_TYPE_DEPTH: usize = 32;
+
+    pub fn build(pool: &ConstantPool) -> anyhow::Result<Self> {
+        Ok(Self {
+            usages: UsageIndex::build(pool)?,
+            localization: localization::build_catalog(pool)?,
+            implementations: collect_implementations(pool)?,
+            types: type_lookup(pool),
+            max_type_depth: Self::DEFAULT_MAX_TYPE_DEPTH,
+        })
+    }
+
+    /// Loads the analysis of a bundle from a cache directory, running and caching it if
+    /// there is no entry for the bundle hash yet or the entry can't be read.
+    pub fn cached(pool: &ConstantPool, bundle_hash: &str, dir: &Path) -> anyhow::Result<Self> {
+        let path = dir.join(format!("{}.json", bundle_hash));
+        if let Some(analysis) = read_json(&path).ok().and_then(|cached| Self::from_json(&cached, pool)) {
+            return Ok(analysis);
+        }
+        let analysis = Self::build(pool)?;
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(path, serde_json::to_string(&analysis.to_json())?)?;
+        Ok(analysis)
+    }
+
+    fn to_json(&self) -> Value {
+        let implementations: Vec<Value> = self
+            .implementations
+            .iter()
+            .map(|(idx, found)| {
+                json!([
+                    u32::from(*idx),
+                    found.iter().map(|idx| u32::from(*idx)).collect::<Vec<_>>()
+                ])
+            })
+            .collect();
+        json!({
+            "generatorVersion": env!("CARGO_PKG_VERSION"),
+            "usages": self.usages.to_json(),
+            "localization": self.localization,
+            "implementations": implementations,
+        })
+    }
+
+    fn from_json(value: &Value, pool: &ConstantPool) -> Option<Self> {
+        // the results may change between generator versions
+        if value["generatorVersion"] != env!("CARGO_PKG_VERSION") {
+            return None;
+        }
+        let implementations = value["implementations"]
+            .as_array()?
+            .iter()
+            .map(|pair| {
+                let found = pair[1]
+                    .as_array()?
+                    .iter()
+                    .map(|idx| Some(PoolIndex::new(idx.as_u64()? as u32)))
+                    .collect::<Option<_>>()?;
+                Some((PoolIndex::new(pair[0].as_u64()? as u32), found))
+            })
+            .collect::<Option<_>>()?;
+        Some(Self {
+            usages: UsageIndex::from_json(&value["usages"])?,
+            localization: Some(value["localization"].clone()).filter(|catalog| !catalog.is_null())?,
+            implementations,
+            types: type_lookup(pool),
+            max_type_depth: Self::DEFAULT_MAX_TYPE_DEPTH,
+        })
+    }
+
+    /// Sets how deeply types may nest before encoding them fails.
+    pub fn with_max_type_depth(mut self, depth: usize) -> Self {
+        self.max_type_depth = depth;
+        self
+    }
+
+    pub fn max_type_depth(&self) -> usize {
+        self.max_type_depth
+    }
+
+    /// Maps class and enum names to their definitions, see `usage::type_lookup`.
+    pub fn types(&self) -> &HashMap<PoolIndex<CName>, PoolIndex<Definition>> {
+        &self.types
+    }
+
+    /// Returns the methods providing a body for a method that is only declared.
+    pub fn implementations<A>(&self, idx: PoolIndex<A>) -> Option<&[PoolIndex<Definition>]> {
+        let idx: PoolIndex<Definition> = idx.cast();
+        self.implementations.get(&idx).map(Vec::as_slice)
+    }
+}
+
+/// Returns true for scripted functions that are declared without a body.
+pub fn is_declaration(fun: &Function) -> bool {
+    !fun.flags.is_native() && fun.code.0.is_empty()
+}
+
+/// Returns true for scripted functions whose body does nothing or only returns a constant.
+/// Declarations have no body at all and aren't stubs.
+pub fn is_stub(fun: &Function) -> bool {
+    if fun.flags.is_native() || is_declaration(fun) {
+        return false;
+    }
+    let instrs: Vec<&Instr<Offset>> = significant_instrs(fun).collect();
+    match instrs.as_slice() {
+        [] | [Instr::Return] => true,
+        [Instr::Return, value] => is_constant(value),
+        _ => false,
+    }
+}
+
+/// Returns the field read by methods whose body only returns a single field of `this`.
+pub fn accessed_field(fun: &Function) -> Option<PoolIndex<Field>> {
+    if fun.flags.is_native() || fun.flags.is_static() {
+        return None;
+    }
+    let instrs: Vec<&Instr<Offset>> = significant_instrs(fun).collect();
+    match instrs.as_slice() {
+        [Instr::Return, Instr::ObjectField(field)] => Some(*field),
+        [Instr::Return, Instr::Context(_), Instr::This, Instr::ObjectField(field)] => Some(*field),
+        _ => None,
+    }
+}
+
+/// Summarizes simple facts about the bytecode of a scripted function: whether every return yields
+/// a constant, whether it branches, whether every call it makes goes to a native function and how
+/// many times it returns. Virtual calls can't be resolved statically, so they count as scripted.
+pub fn behavior_hints(fun: &Function, pool: &ConstantPool) -> anyhow::Result<Option<Value>> {
+    if fun.flags.is_native() || fun.code.0.is_empty() {
+        return Ok(None);
+    }
+    let instrs: Vec<&Instr<Offset>> = significant_instrs(fun).collect();
+    let returns: Vec<usize> = instrs
+        .iter()
+        .enumerate()
+        .filter(|(_, instr)| matches!(instr, Instr::Return))
+        .map(|(i, _)| i)
+        .collect();
+    let always_returns_constant = fun.return_type.is_some()
+        && !returns.is_empty()
+        && returns
+            .iter()
+            .all(|&i| instrs.get(i + 1).is_some_and(|value| is_constant(value)));
+    let has_no_branches = !instrs.iter().any(|instr| {
+        matches!(
+            instr,
+            Instr::Jump(_)
+                | Instr::JumpIfFalse(_)
+                | Instr::Conditional(..)
+                | Instr::Switch(..)
+                | Instr::SwitchLabel(..)
+        )
+    });
+
+    let mut calls = 0;
+    let mut native_calls = 0;
+    for instr in &instrs {
+        match instr {
+            Instr::InvokeStatic(_, _, callee, ..) => {
+                calls += 1;
+                if pool.function(*callee)?.flags.is_native() {
+                    native_calls += 1;
+                }
+            }
+            Instr::InvokeVirtual(..) => calls += 1,
+            _ => {}
+        }
+    }
+    Ok(Some(json!({
+        "alwaysReturnsConstant": always_returns_constant,
+        "hasNoBranches": has_no_branches,
+        "callsOnlyNatives": calls > 0 && calls == native_calls,
+        "returnCount": returns.len(),
+    })))
+}
+
+/// Returns the value an optional parameter of the given type takes when the argument is omitted,
+/// rendered as a redscript literal.
+///
+/// The compiler passes nothing for omitted arguments and the engine zero-initializes them, so this is
+/// the zero value of the type, or the member of an enum with the value 0.
+pub fn implicit_default(
+    type_: PoolIndex<Type>,
+    types: &HashMap<PoolIndex<CName>, PoolIndex<Definition>>,
+    pool: &ConstantPool,
+) -> anyhow::Result<Option<String>> {
+    let def = pool.definition(type_)?;
+    let default = match &def.value {
+        AnyDefinition::Type(Type::Prim) => match pool.names.get(def.name)?.as_ref() {
+            "Bool" => "false",
+            "Float" | "Double" => "0.0",
+            "String" => "\"\"",
+            "CName" => "n\"None\"",
+            "TweakDBID" => "t\"\"",
+            "ResRef" => "r\"\"",
+            name if name.starts_with("Int") || name.starts_with("Uint") => "0",
+            _ => return Ok(None),
+        }
+        .to_owned(),
+        AnyDefinition::Type(Type::Ref(_) | Type::WeakRef(_)) => "null".to_owned(),
+        AnyDefinition::Type(Type::Array(_)) => "[]".to_owned(),
+        AnyDefinition::Type(Type::Class) => {
+            let Some(&idx) = types.get(&def.name) else {
+                return Ok(None);
+            };
+            let AnyDefinition::Enum(enum_) = &pool.definition(idx)?.value else {
+                return Ok(None);
+            };
+            let mut zero = None;
+            for member in &enum_.members {
+                let member = pool.definition(*member)?;
+                if member.value.as_enum_value() == Some(&0) {
+                    zero = Some(format!(
+                        "{}.{}",
+                        pool.names.get(def.name)?,
+                        pool.names.get(member.name)?
+                    ));
+                    break;
+                }
+            }
+            return Ok(zero);
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(default))
+}
+
+fn significant_instrs(fun: &Function) -> impl Iterator<Item = &Instr<Offset>> {
+    fun.code.0.iter().filter(|instr| !matches!(instr, Instr::Nop))
+}
+
+fn is_constant(instr: &Instr<Offset>) -> bool {
+    matches!(
+        instr,
+        Instr::Null
+            | Instr::I32One
+            | Instr::I32Zero
+            | Instr::I8Const(_)
+            | Instr::I16Const(_)
+            | Instr::I32Const(_)
+            | Instr::I64Const(_)
+            | Instr::U8Const(_)
+            | Instr::U16Const(_)
+            | Instr::U32Const(_)
+            | Instr::U64Const(_)
+            | Instr::F32Const(_)
+            | Instr::F64Const(_)
+            | Instr::NameConst(_)
+            | Instr::EnumConst(..)
+            | Instr::StringConst(_)
+            | Instr::TweakDbIdConst(_)
+            | Instr::ResourceConst(_)
+            | Instr::TrueConst
+            | Instr::FalseConst
+    )
+}
+
+fn collect_implementations(
+    pool: &ConstantPool,
+) -> anyhow::Result<HashMap<PoolIndex<Definition>, Vec<PoolIndex<Definition>>>> {
+    let mut overrides: HashMap<PoolIndex<Definition>, Vec<PoolIndex<Definition>>> = HashMap::new();
+    for (idx, def) in pool.definitions() {
+        if let AnyDefinition::Function(Function {
+            base_method: Some(base),
+            ..
+        }) = &def.value
+        {
+            overrides.entry(base.cast()).or_default().push(idx);
+        }
+    }
+
+    let mut implementations = HashMap::new();
+    for (idx, def) in pool.definitions() {
+        let AnyDefinition::Function(fun) = &def.value else {
+            continue;
+        };
+        if !is_declaration(fun) {
+            continue;
+        }
+        let mut found = vec![];
+        let mut visited = HashSet::new();
+        let mut pending: Vec<PoolIndex<Definition>> = overrides.get(&idx).cloned().unwrap_or_default();
+        while let Some(next) = pending.pop() {
+            if !visited.insert(next) {
+                continue;
+            }
+            if !is_declaration(pool.function(next.cast())?) {
+                found.push(next);
+            }
+            pending.extend(overrides.get(&next).into_iter().flatten().copied());
+        }
+        if !found.is_empty() {
+            implementations.insert(idx, found);
+        }
+    }
+    Ok(implementations)
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use redscript::bundle::{CName, ConstantPool, PoolIndex};
+use redscript::bytecode::Instr;
+use redscript::definition::{AnyDefinition, Class, Definition, Type};
+use serde_json::{json, Value};
+
+use crate::usage::resolve_type;
+
+/// Returns the types of the fields and method signatures of a class.
+pub fn signature_types(class: &Class, pool: &ConstantPool) -> anyhow::Result<Vec<PoolIndex<Type>>> {
+    let mut types = vec![];
+    for field in &class.fields {
+        types.push(pool.field(*field)?.type_);
+    }
+    for method in &class.functions {
+        let fun = pool.function(*method)?;
+        types.extend(fun.return_type);
+        for param in &fun.parameters {
+            types.push(pool.parameter(*param)?.type_);
+        }
+    }
+    Ok(types)
+}
+
+/// Lists the classes and enums a class depends on through its fields and method signatures and,
+/// if `bytecode` is set, through the constructions, static calls, field accesses and enum
+/// constants in its method bodies.
+pub fn class_dependencies(
+    idx: PoolIndex<Definition>,
+    class: &Class,
+    types: &HashMap<PoolIndex<CName>, PoolIndex<Definition>>,
+    pool: &ConstantPool,
+    bytecode: bool,
+) -> anyhow::Result<Value> {
+    let mut found = vec![];
+    for type_ in signature_types(class, pool)? {
+        found.extend(resolve_type(type_, types, pool)?);
+    }
+    if bytecode {
+        for method in &class.functions {
+            for instr in &pool.function(*method)?.code.0 {
+                let dependency: PoolIndex<Definition> = match instr {
+                    Instr::New(class) | Instr::Construct(_, class) => class.cast(),
+                    Instr::EnumConst(enum_, _) => enum_.cast(),
+                    Instr::InvokeStatic(_, _, callee, ..) => pool.definition(*callee)?.parent,
+                    Instr::ObjectField(field) | Instr::StructField(field) => pool.definition(*field)?.parent,
+                    _ => continue,
+                };
+                found.push(dependency);
+            }
+        }
+    }
+
+    let mut indexes: Vec<u32> = found
+        .into_iter()
+        .filter(|dep| *dep != idx && *dep != PoolIndex::UNDEFINED)
+        .map(u32::from)
+        .collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+
+    let mut dependencies = vec![];
+    for dep in indexes {
+        let def = pool.definition(PoolIndex::<Definition>::new(dep))?;
+        if matches!(&def.value, AnyDefinition::Class(_) | AnyDefinition::Enum(_)) {
+            dependencies.push(json!({"name": pool.names.get(def.name)?.as_ref(), "index": dep}));
+        }
+    }
+    Ok(Value::Array(dependencies))
+}
@@ -0,0 +1,460 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::enums;
+use crate::render::{render_type, unmangled};
+
+const REMOVED_REASON: &str = "removed in a newer version";
+
+/// Root documents of a generated output directory, keyed by definition name. Enum members are
+/// kept in their canonical order, see `enums::canonical_members`.
+pub struct Snapshot {
+    docs: BTreeMap<String, Value>,
+}
+
+impl Snapshot {
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let index = read_json(&dir.join("index.json"))?;
+        let mut docs = BTreeMap::new();
+        for entry in index.as_array().into_iter().flatten() {
+            let Some(idx) = entry["index"].as_u64() else {
+                continue;
+            };
+            let mut doc = read_json(&dir.join(format!("{}.json", idx)))?;
+            if doc["tag"] == "Enum" {
+                enums::canonical_members(&mut doc);
+            }
+            if let Some(name) = doc["name"].as_str() {
+                docs.insert(name.to_owned(), doc);
+            }
+        }
+        Ok(Self { docs })
+    }
+
+    /// Returns every root definition and class member along with its kind and a signature
+    /// string describing its shape.
+    pub fn signatures(&self) -> Vec<(String, String, String)> {
+        let mut signatures = vec![];
+        for (name, doc) in &self.docs {
+            signatures.push((name.clone(), tag(doc), signature(doc)));
+            for member in ["fields", "methods"]
+                .iter()
+                .flat_map(|key| doc[*key].as_array().into_iter().flatten())
+            {
+                if let Some(member_name) = member["name"].as_str() {
+                    signatures.push((format!("{}::{}", name, member_name), tag(member), signature(member)));
+                }
+            }
+        }
+        signatures
+    }
+
+    /// Returns true if a document has the same pretty name as the given mangled one.
+    fn has_overload(&self, name: &str) -> bool {
+        let pretty = unmangled(name);
+        self.docs.contains_key(pretty)
+            || self
+                .docs
+                .range(format!("{};", pretty)..)
+                .next()
+                .is_some_and(|(other, _)| unmangled(other) == pretty)
+    }
+
+    /// Marks the document and those of its members that are missing from this (newer) snapshot
+    /// as deprecated. Overloads that only changed their signature are matched by name and not
+    /// deprecated.
+    pub fn annotate_deprecated(&self, doc: &mut Value) {
+        let Some(name) = doc["name"].as_str() else {
+            return;
+        };
+        let Some(newer) = self.docs.get(name) else {
+            if !self.has_overload(name) {
+                doc["deprecated"] = json!({ "reason": REMOVED_REASON });
+            }
+            return;
+        };
+        let (members, newer_members) = (member_names(doc), member_names(newer));
+        let mut removed: Vec<String> = members.difference(&newer_members).cloned().collect();
+        let mut added: Vec<String> = newer_members.difference(&members).cloned().collect();
+        match_overloads(&mut removed, &mut added);
+        for key in ["fields", "methods", "members"] {
+            for member in doc.get_mut(key).and_then(Value::as_array_mut).into_iter().flatten() {
+                if member["name"]
+                    .as_str()
+                    .is_some_and(|name| removed.iter().any(|removed| removed == name))
+                {
+                    member["deprecated"] = json!({ "reason": REMOVED_REASON });
+                }
+            }
+        }
+    }
+}
+
+/// API differences between two snapshots.
+#[derive(Default)]
+pub struct Diff {
+    added: Vec<Symbol>,
+    removed: Vec<Symbol>,
+    changed: Vec<MemberChange>,
+    added_members: BTreeMap<String, Vec<String>>,
+    removed_members: BTreeMap<String, Vec<String>>,
+    changed_members: BTreeMap<String, Vec<MemberChange>>,
+}
+
+impl Diff {
+    pub fn compute(previous: &Snapshot, current: &Snapshot) -> Self {
+        let mut diff = Self::default();
+        for (name, doc) in &current.docs {
+            let Some(old) = previous.docs.get(name) else {
+                continue;
+            };
+            let old_members = member_names(old);
+            let new_members = member_names(doc);
+            let mut added: Vec<String> = new_members.difference(&old_members).cloned().collect();
+            let mut removed: Vec<String> = old_members.difference(&new_members).cloned().collect();
+            let overloads = match_overloads(&mut removed, &mut added);
+            if !added.is_empty() {
+                diff.added_members.insert(name.clone(), added);
+            }
+            if !removed.is_empty() {
+                diff.removed_members.insert(name.clone(), removed);
+            }
+            let old_signatures = member_signatures(old);
+            let new_signatures = member_signatures(doc);
+            let mut changed: Vec<MemberChange> = new_signatures
+                .iter()
+                .filter_map(|(member, (tag, after))| {
+                    let (_, before) = old_signatures.get(member)?;
+                    (before != after).then(|| MemberChange {
+                        name: member.clone(),
+                        tag: tag.clone(),
+                        before: before.clone(),
+                        after: after.clone(),
+                    })
+                })
+                .collect();
+            for (old_member, new_member) in overloads {
+                let (Some((_, before)), Some((tag, after))) =
+                    (old_signatures.get(&old_member), new_signatures.get(&new_member))
+                else {
+                    continue;
+                };
+                changed.push(MemberChange {
+                    name: new_member,
+                    tag: tag.clone(),
+                    before: before.clone(),
+                    after: after.clone(),
+                });
+            }
+            if !changed.is_empty() {
+                diff.changed_members.insert(name.clone(), changed);
+            }
+        }
+
+        // root functions are keyed by their mangled name too, so overloads are matched the same way
+        let mut added: Vec<String> = current
+            .docs
+            .keys()
+            .filter(|name| !previous.docs.contains_key(*name))
+            .cloned()
+            .collect();
+        let mut removed: Vec<String> = previous
+            .docs
+            .keys()
+            .filter(|name| !current.docs.contains_key(*name))
+            .cloned()
+            .collect();
+        for (old_name, new_name) in match_overloads(&mut removed, &mut added) {
+            let (old, doc) = (&previous.docs[&old_name], &current.docs[&new_name]);
+            diff.changed.push(MemberChange {
+                name: new_name,
+                tag: tag(doc),
+                before: signature(old),
+                after: signature(doc),
+            });
+        }
+        diff.added = added
+            .iter()
+            .map(|name| Symbol::new(name, &current.docs[name]))
+            .collect();
+        diff.removed = removed
+            .iter()
+            .map(|name| Symbol::new(name, &previous.docs[name]))
+            .collect();
+        diff
+    }
+
+    pub fn render_markdown(&self) -> anyhow::Result<String> {
+        let mut out = String::from("# What's new\n");
+        if self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.added_members.is_empty()
+            && self.removed_members.is_empty()
+            && self.changed_members.is_empty()
+        {
+            writeln!(out, "\nNo API changes.")?;
+            return Ok(out);
+        }
+
+        for (tag, title) in [
+            ("Class", "Added classes"),
+            ("Enum", "Added enums"),
+            ("Function", "Added functions"),
+        ] {
+            let mut symbols = self.added.iter().filter(|sym| sym.tag == tag).peekable();
+            if symbols.peek().is_some() {
+                writeln!(out, "\n## {}\n", title)?;
+                for sym in symbols {
+                    writeln!(out, "- `{}`", unmangled(&sym.name))?;
+                }
+            }
+        }
+
+        if !self.changed.is_empty() {
+            writeln!(out, "\n## Changed signatures\n")?;
+            for change in &self.changed {
+                writeln!(
+                    out,
+                    "- `{}`: `{}` → `{}`",
+                    unmangled(&change.name),
+                    change.before,
+                    change.after
+                )?;
+            }
+        }
+
+        if !self.added_members.is_empty() {
+            writeln!(out, "\n## New members\n")?;
+            for (owner, members) in &self.added_members {
+                writeln!(out, "- `{}`: {}", unmangled(owner), render_names(members))?;
+            }
+        }
+
+        if !self.changed_members.is_empty() {
+            writeln!(out, "\n## Changed members\n")?;
+            for (owner, changes) in &self.changed_members {
+                for change in changes {
+                    writeln!(
+                        out,
+                        "- `{}::{}`: `{}` → `{}`",
+                        unmangled(owner),
+                        unmangled(&change.name),
+                        change.before,
+                        change.after
+                    )?;
+                }
+            }
+        }
+
+        if !self.removed.is_empty() || !self.removed_members.is_empty() {
+            writeln!(out, "\n## Removed API\n")?;
+            for sym in &self.removed {
+                writeln!(out, "- {} `{}`", sym.tag.to_lowercase(), unmangled(&sym.name))?;
+            }
+            for (owner, members) in &self.removed_members {
+                writeln!(out, "- members of `{}`: {}", unmangled(owner), render_names(members))?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Summarizes the number of changes of each kind in a few lines of text.
+    pub fn summary(&self) -> String {
+        let changed: usize = self.changed_members.values().map(Vec::len).sum();
+        let added: usize = self.added_members.values().map(Vec::len).sum();
+        let removed: usize = self.removed_members.values().map(Vec::len).sum();
+        if self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && added == 0
+            && removed == 0
+            && changed == 0
+        {
+            return "No API changes.".to_owned();
+        }
+        [
+            format!(
+                "{} definitions added, {} removed, {} changed",
+                self.added.len(),
+                self.removed.len(),
+                self.changed.len()
+            ),
+            format!("{} members added, {} removed", added, removed),
+            format!("{} member signatures changed", changed),
+        ]
+        .join("\n")
+    }
+
+    /// Returns the differences as a machine-readable document, with the member-level changes
+    /// grouped by the class or enum they belong to.
+    pub fn to_json(&self) -> Value {
+        let mut owners: BTreeMap<&str, Value> = BTreeMap::new();
+        for (owner, members) in &self.added_members {
+            owners.entry(owner.as_str()).or_insert_with(empty_changes)["added"] = json!(members);
+        }
+        for (owner, members) in &self.removed_members {
+            owners.entry(owner.as_str()).or_insert_with(empty_changes)["removed"] = json!(members);
+        }
+        for (owner, changes) in &self.changed_members {
+            let changes: Vec<Value> = changes.iter().map(MemberChange::to_json).collect();
+            owners.entry(owner.as_str()).or_insert_with(empty_changes)["changed"] = changes.into();
+        }
+        let symbols = |symbols: &[Symbol]| -> Vec<Value> {
+            symbols
+                .iter()
+                .map(|sym| json!({"name": sym.name, "tag": sym.tag}))
+                .collect()
+        };
+        json!({
+            "added": symbols(&self.added),
+            "removed": symbols(&self.removed),
+            "changed": self.changed.iter().map(MemberChange::to_json).collect::<Vec<_>>(),
+            "members": owners,
+        })
+    }
+
+    /// Renders the API removed between the snapshots as a deprecations page.
+    pub fn render_deprecations(&self) -> anyhow::Result<String> {
+        let mut out = String::from("# Deprecations\n");
+        if self.removed.is_empty() && self.removed_members.is_empty() {
+            writeln!(out, "\nNothing is deprecated.")?;
+            return Ok(out);
+        }
+        writeln!(
+            out,
+            "\nThe following API is removed in a newer version and should not be relied on.\n"
+        )?;
+        for sym in &self.removed {
+            writeln!(out, "- {} `{}`", sym.tag.to_lowercase(), unmangled(&sym.name))?;
+        }
+        for (owner, members) in &self.removed_members {
+            writeln!(out, "- members of `{}`: {}", unmangled(owner), render_names(members))?;
+        }
+        Ok(out)
+    }
+}
+
+struct Symbol {
+    name: String,
+    tag: String,
+}
+
+impl Symbol {
+    fn new(name: &str, doc: &Value) -> Self {
+        Self {
+            name: name.to_owned(),
+            tag: tag(doc),
+        }
+    }
+}
+
+struct MemberChange {
+    name: String,
+    tag: String,
+    before: String,
+    after: String,
+}
+
+impl MemberChange {
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "tag": self.tag,
+            "before": self.before,
+            "after": self.after,
+        })
+    }
+}
+
+fn empty_changes() -> Value {
+    json!({"added": [], "removed": [], "changed": []})
+}
+
+fn signature(doc: &Value) -> String {
+    match doc["tag"].as_str() {
+        Some("Function") => {
+            let params: Vec<String> = doc["parameters"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|param| render_type(&param["type"]))
+                .collect();
+            match doc["returnType"] {
+                Value::Null => format!("({})", params.join(", ")),
+                ref ret => format!("({}) -> {}", params.join(", "), render_type(ret)),
+            }
+        }
+        Some("Field") => render_type(&doc["type"]),
+        Some("EnumValue") => doc["value"].to_string(),
+        Some("Class") => doc["bases"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|base| base["name"].as_str())
+            .collect::<Vec<_>>()
+            .join(" < "),
+        Some("Enum") => doc["members"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|member| format!("{}={}", member["name"].as_str().unwrap_or_default(), member["value"]))
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => String::new(),
+    }
+}
+
+fn tag(doc: &Value) -> String {
+    doc["tag"].as_str().unwrap_or_default().to_owned()
+}
+
+pub fn read_json(path: &Path) -> anyhow::Result<Value> {
+    Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+}
+
+fn member_names(doc: &Value) -> BTreeSet<String> {
+    ["fields", "methods", "members"]
+        .iter()
+        .flat_map(|key| doc[*key].as_array().into_iter().flatten())
+        .filter_map(|member| member["name"].as_str().map(str::to_owned))
+        .collect()
+}
+
+/// Maps the names of the members of a document to their kind and signature.
+fn member_signatures(doc: &Value) -> BTreeMap<String, (String, String)> {
+    ["fields", "methods", "members"]
+        .iter()
+        .flat_map(|key| doc[*key].as_array().into_iter().flatten())
+        .filter_map(|member| Some((member["name"].as_str()?.to_owned(), (tag(member), signature(member)))))
+        .collect()
+}
+
+/// Pairs members missing by their mangled name with new members of the same pretty name, which
+/// are overloads whose signature changed, and takes the pairs out of both lists.
+fn match_overloads(removed: &mut Vec<String>, added: &mut Vec<String>) -> Vec<(String, String)> {
+    let mut pairs = vec![];
+    removed.retain(
+        |old| match added.iter().position(|new| unmangled(new) == unmangled(old)) {
+            Some(position) => {
+                pairs.push((old.clone(), added.remove(position)));
+                false
+            }
+            None => true,
+        },
+    );
+    pairs
+}
+
+fn render_names(names: &[String]) -> String {
+    names
+        .iter()
+        .map(|name| format!("`{}`", unmangled(name)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,80 @@
+use redscript::bundle::{ConstantPool, PoolIndex};
+use redscript::definition::{AnyDefinition, Definition};
+use serde_json::Value;
+
+use crate::analysis::Analysis;
+use crate::diff::Snapshot;
+use crate::enums::{self, EnumOrder};
+use crate::ids::StableIds;
+use crate::instantiation::InstantiationIndex;
+use crate::native_db::NativeDb;
+use crate::nested::InnerDefinitions;
+use crate::stability::Stability;
+use crate::{dependencies, encode_definition, layout, render};
+
+/// Produces the documents written for root definitions: the encoded definition annotated
+/// with usages, dependencies, layout, stability and whatever optional data is configured.
+pub struct Documents<'a> {
+    pool: &'a ConstantPool,
+    analysis: &'a Analysis,
+    instantiation: InstantiationIndex,
+    inner: InnerDefinitions,
+    pub enum_order: EnumOrder,
+    pub group_enum_aliases: bool,
+    pub bytecode_dependencies: bool,
+    pub native_db: Option<&'a NativeDb>,
+    pub ids: Option<&'a StableIds>,
+    pub newer: Option<&'a Snapshot>,
+    pub stability: Stability,
+}
+
+impl<'a> Documents<'a> {
+    pub fn new(pool: &'a ConstantPool, analysis: &'a Analysis) -> anyhow::Result<Self> {
+        Ok(Self {
+            pool,
+            analysis,
+            instantiation: InstantiationIndex::build(pool)?,
+            inner: InnerDefinitions::build(pool)?,
+            enum_order: EnumOrder::Declaration,
+            group_enum_aliases: false,
+            bytecode_dependencies: false,
+            native_db: None,
+            ids: None,
+            newer: None,
+            stability: Stability::default(),
+        })
+    }
+
+    pub fn encode(&self, idx: PoolIndex<Definition>, def: &Definition) -> anyhow::Result<Value> {
+        let (pool, analysis) = (self.pool, self.analysis);
+        let mut encoded = encode_definition(def, pool, analysis)?;
+        encoded["usageCount"] = analysis.usages.count(idx).into();
+        if matches!(&def.value, AnyDefinition::Enum(_)) {
+            enums::arrange_members(&mut encoded, self.enum_order, self.group_enum_aliases);
+        }
+        if let AnyDefinition::Class(class) = &def.value {
+            encoded["dependencies"] =
+                dependencies::class_dependencies(idx, class, analysis.types(), pool, self.bytecode_dependencies)?;
+            encoded["instantiation"] = self.instantiation.hint(idx, class, pool)?;
+            if let Some(inner) = self.inner.encode(idx, pool, analysis)? {
+                encoded["inner"] = inner;
+            }
+        }
+        if let Some(native_db) = self.native_db {
+            native_db.merge_class(&mut encoded);
+        }
+        if let Some(layout) = layout::struct_layout(&encoded) {
+            encoded["layout"] = layout;
+        }
+        if let Some(ids) = self.ids {
+            ids.annotate(&mut encoded);
+        }
+        if let Some(newer) = self.newer {
+            newer.annotate_deprecated(&mut encoded);
+        }
+        let name = pool.names.get(def.name)?;
+        encoded["stability"] = self.stability.assess(&name, def, analysis.usages.count(idx)).to_json();
+        encoded["summaryHint"] = render::summary_hint(&encoded).into();
+        Ok(encoded)
+    }
+}
@@ -0,0 +1,110 @@
+use serde_json::{json, Value};
+
+use crate::render::{class_header, function_signature, pretty_name, render_type};
+
+// limits imposed by Discord on the parts of an embed
+const TITLE_LIMIT: usize = 256;
+const DESCRIPTION_LIMIT: usize = 4096;
+const FIELD_VALUE_LIMIT: usize = 1024;
+// keeps the whole embed well under the 6000 character total
+const SIGNATURE_LIMIT: usize = 1024;
+const MAX_LISTED_MEMBERS: usize = 10;
+
+/// Builds a Discord embed summarizing an encoded document with its signature and a few key facts.
+///
+/// `link` is a URL template in which `{index}` and `{name}` are replaced with the index and
+/// the name of the definition.
+pub fn build_embed(doc: &Value, index: u32, link: Option<&str>) -> Value {
+    let name = pretty_name(&doc["name"]);
+    let signature = signature(doc);
+    let summary = doc["summaryHint"].as_str().unwrap_or_default();
+    let description = format!("```swift\n{}\n```\n{}", truncate(&signature, SIGNATURE_LIMIT), summary);
+
+    let mut fields = vec![];
+    match doc["tag"].as_str() {
+        Some("Class") => {
+            if let Some(base) = doc["bases"].get(0).and_then(|base| base["name"].as_str()) {
+                fields.push(field("Extends", base.to_owned()));
+            }
+            let counts = format!("{} fields, {} methods", count(doc, "fields"), count(doc, "methods"));
+            fields.push(field("Members", counts));
+        }
+        Some("Function") => {
+            if !doc["returnType"].is_null() {
+                fields.push(field("Returns", format!("`{}`", render_type(&doc["returnType"]))));
+            }
+            if let Some(source) = doc["source"].as_str() {
+                fields.push(field("Source", format!("`{}`", source)));
+            }
+        }
+        Some("Enum") => {
+            let members: Vec<String> = doc["members"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .take(MAX_LISTED_MEMBERS)
+                .map(|member| format!("`{}`", pretty_name(&member["name"])))
+                .collect();
+            let mut listed = members.join(", ");
+            if count(doc, "members") > members.len() {
+                listed.push_str(", …");
+            }
+            fields.push(field("Members", listed));
+        }
+        _ => {}
+    }
+    if let Some(usages) = doc["usageCount"].as_u64() {
+        fields.push(field("Usages", usages.to_string()));
+    }
+
+    json!({
+        "title": truncate(name, TITLE_LIMIT),
+        "description": truncate(&description, DESCRIPTION_LIMIT),
+        "url": expand_link(link, doc, index),
+        "fields": fields,
+    })
+}
+
+/// Builds a hover-card document for embedding on external sites, holding just the name,
+/// signature, summary and link of a definition. `link` is expanded like for embeds.
+pub fn build_card(doc: &Value, index: u32, link: Option<&str>) -> Value {
+    json!({
+        "name": pretty_name(&doc["name"]),
+        "kind": doc["tag"],
+        "signature": signature(doc),
+        "summary": doc["summaryHint"],
+        "url": expand_link(link, doc, index),
+    })
+}
+
+fn signature(doc: &Value) -> String {
+    match doc["tag"].as_str() {
+        Some("Class") => class_header(doc),
+        Some("Function") => function_signature(doc),
+        _ => format!("enum {}", pretty_name(&doc["name"])),
+    }
+}
+
+fn expand_link(link: Option<&str>, doc: &Value, index: u32) -> Option<String> {
+    link.map(|link| {
+        link.replace("{index}", &index.to_string())
+            .replace("{name}", pretty_name(&doc["name"]))
+    })
+}
+
+fn field(name: &str, value: String) -> Value {
+    json!({"name": name, "value": truncate(&value, FIELD_VALUE_LIMIT), "inline": true})
+}
+
+fn count(doc: &Value, key: &str) -> usize {
+    doc[key].as_array().map_or(0, Vec::len)
+}
+
+fn truncate(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_owned();
+    }
+    let mut truncated: String = text.chars().take(limit - 1).collect();
+    truncated.push('…');
+    truncated
+}
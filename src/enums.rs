@@ -0,0 +1,71 @@
+use std::str::FromStr;
+
+use serde_json::{json, Value};
+
+/// Order in which enum members are emitted.
+#[derive(Debug, Clone, Copy)]
+pub enum EnumOrder {
+    Declaration,
+    Value,
+    Name,
+}
+
+impl FromStr for EnumOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "declaration" => Ok(EnumOrder::Declaration),
+            "value" => Ok(EnumOrder::Value),
+            "name" => Ok(EnumOrder::Name),
+            other => Err(format!("unknown enum order {}", other)),
+        }
+    }
+}
+
+/// Reorders the members of an encoded enum and optionally folds members sharing a value
+/// into the `aliases` of the first one declared.
+pub fn arrange_members(doc: &mut Value, order: EnumOrder, group_aliases: bool) {
+    let Some(members) = doc["members"].as_array_mut() else {
+        return;
+    };
+    if group_aliases {
+        let mut grouped: Vec<Value> = vec![];
+        for member in members.drain(..) {
+            match grouped.iter_mut().find(|primary| primary["value"] == member["value"]) {
+                Some(primary) => match primary["aliases"].as_array_mut() {
+                    Some(aliases) => aliases.push(member["name"].clone()),
+                    None => primary["aliases"] = json!([member["name"]]),
+                },
+                None => grouped.push(member),
+            }
+        }
+        *members = grouped;
+    }
+    match order {
+        EnumOrder::Declaration => {}
+        EnumOrder::Value => members.sort_by_key(|member| member["value"].as_i64()),
+        EnumOrder::Name => members.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str())),
+    }
+}
+
+/// Undoes `arrange_members` for comparisons between outputs: aliases are expanded back into
+/// members of their own and members are sorted by name, so that neither the order nor the
+/// grouping an output was generated with shows up as a change.
+pub fn canonical_members(doc: &mut Value) {
+    let Some(members) = doc["members"].as_array_mut() else {
+        return;
+    };
+    let mut expanded = vec![];
+    for mut member in members.drain(..) {
+        let aliases = member.as_object_mut().and_then(|member| member.remove("aliases"));
+        for alias in aliases.as_ref().and_then(Value::as_array).into_iter().flatten() {
+            let mut aliased = member.clone();
+            aliased["name"] = alias.clone();
+            expanded.push(aliased);
+        }
+        expanded.push(member);
+    }
+    expanded.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+    *members = expanded;
+}
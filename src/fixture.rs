@@ -0,0 +1,367 @@
+use redscript::bundle::{CName, ConstantPool, PoolIndex};
+use redscript::bytecode::{Code, Instr, Offset};
+use redscript::definition::{
+    Class, ClassFlags, Definition, Enum, Field, FieldFlags, Function, FunctionFlags, Parameter, ParameterFlags, Type,
+    Visibility,
+};
+
+/// Builds a synthetic pool for benchmarks and tests, so that no game data has to be shipped.
+///
+/// It contains a chain of `classes` classes each extending the previous one, with fields of
+/// primitive, array and reference types, a field getter, a constructor-like static function,
+/// a method with an optional parameter and an overridden declaration. Next to them are an enum
+/// with an alias, a native class with edge-case field and parameter flags and a few global
+/// functions.
+pub fn synthetic_pool(classes: usize) -> ConstantPool {
+    let mut builder = Builder::default();
+    let int = builder.prim("Int32");
+    let bool_ = builder.prim("Bool");
+    let string = builder.prim("String");
+    let ints = builder.type_("array:Int32", Type::Array(int));
+    let fixed_ints = builder.type_("[4]Int32", Type::StaticArray(int, 4));
+
+    builder.enum_("FixtureMode", &[("None", 0), ("Fast", 1), ("Slow", 2), ("Default", 0)]);
+    let mode_type = builder.type_("FixtureMode", Type::Class);
+
+    let mut base = PoolIndex::UNDEFINED;
+    let mut declaration = None;
+    for i in 0..classes {
+        let name = format!("FixtureClass{}", i);
+        let class: PoolIndex<Class> = builder.pool.reserve();
+        let class_type = builder.type_(&name, Type::Class);
+        let class_ref = builder.type_(&format!("ref:{}", name), Type::Ref(class_type));
+
+        let value = builder.field(class, "value", int);
+        let fields = vec![
+            value,
+            builder.field(class, "items", ints),
+            builder.field(class, "fixed", fixed_ints),
+            builder.field(class, "label", string),
+            builder.field(class, "next", class_ref),
+            builder.field(class, "mode", mode_type),
+        ];
+
+        let getter = builder.function(
+            class,
+            "GetValue;",
+            FunctionFlags::new().with_is_final(true),
+            Some(int),
+            &[],
+            vec![Instr::Return, Instr::ObjectField(value)],
+            None,
+        );
+        let create = builder.function(
+            class,
+            "Create;",
+            FunctionFlags::new().with_is_static(true),
+            Some(class_ref),
+            &[],
+            vec![Instr::Return, Instr::New(class)],
+            None,
+        );
+        let update = builder.function(
+            class,
+            "Update;Int32Bool",
+            FunctionFlags::new(),
+            None,
+            &[
+                ("amount", int, ParameterFlags::new()),
+                ("force", bool_, ParameterFlags::new().with_is_optional(true)),
+            ],
+            vec![Instr::Nop, Instr::Return],
+            None,
+        );
+        // the first class only declares the callback, the others implement it
+        let callback = match declaration {
+            None => builder.function(
+                class,
+                "OnUpdate;",
+                FunctionFlags::new().with_is_callback(true),
+                None,
+                &[],
+                vec![],
+                None,
+            ),
+            Some(base_method) => builder.function(
+                class,
+                "OnUpdate;",
+                FunctionFlags::new().with_is_callback(true),
+                None,
+                &[],
+                vec![Instr::Return, Instr::I32Zero],
+                Some(base_method),
+            ),
+        };
+        declaration.get_or_insert(callback);
+
+        let flags = ClassFlags::new().with_is_struct(i % 4 == 3).with_is_abstract(i == 0);
+        let definition = Definition::class(
+            builder.name(&name),
+            Class {
+                visibility: Visibility::Public,
+                flags,
+                base,
+                functions: vec![getter, create, update, callback],
+                fields,
+                overrides: vec![],
+            },
+        );
+        builder.pool.put_definition(class, definition);
+        base = class;
+    }
+
+    // a native class with edge-case flags on its members
+    let native: PoolIndex<Class> = builder.pool.reserve();
+    let native_fields = vec![
+        builder.field_with(
+            native,
+            "handle",
+            int,
+            FieldFlags::new().with_is_native(true).with_is_const(true),
+        ),
+        builder.field_with(
+            native,
+            "saved",
+            string,
+            FieldFlags::new().with_is_persistent(true).with_is_editable(true),
+        ),
+        builder.field_with(
+            native,
+            "synced",
+            bool_,
+            FieldFlags::new().with_is_replicated(true).with_is_inline(true),
+        ),
+    ];
+    let native_method = builder.function(
+        native,
+        "TryGet;Int32Int32",
+        FunctionFlags::new().with_is_native(true).with_is_final(true),
+        Some(bool_),
+        &[
+            ("key", int, ParameterFlags::new()),
+            ("result", int, ParameterFlags::new().with_is_out(true)),
+        ],
+        vec![],
+        None,
+    );
+    let native_definition = Definition::class(
+        builder.name("FixtureNative"),
+        Class {
+            visibility: Visibility::Public,
+            flags: ClassFlags::new().with_is_native(true).with_is_final(true),
+            base: PoolIndex::UNDEFINED,
+            functions: vec![native_method],
+            fields: native_fields,
+            overrides: vec![],
+        },
+    );
+    builder.pool.put_definition(native, native_definition);
+
+    let global = PoolIndex::UNDEFINED;
+    builder.function(
+        global,
+        "FixtureLog;String",
+        FunctionFlags::new().with_is_static(true).with_is_native(true),
+        None,
+        &[("message", string, ParameterFlags::new())],
+        vec![],
+        None,
+    );
+    builder.function(
+        global,
+        "FixtureMax;Int32Int32",
+        FunctionFlags::new().with_is_static(true),
+        Some(int),
+        &[("a", int, ParameterFlags::new()), ("b", int, ParameterFlags::new())],
+        vec![Instr::Return, Instr::I32Zero],
+        None,
+    );
+    builder.function(
+        global,
+        "FixtureMode;",
+        FunctionFlags::new().with_is_static(true).with_is_exec(true),
+        Some(mode_type),
+        &[],
+        vec![Instr::Return],
+        None,
+    );
+    builder.pool
+}
+
+/// Builds a pool with a class whose field, method parameter and return type use a type referring
+/// to itself, as found in malformed bundles. Next to it are a method returning its own class
+/// instead of a type and two classes extending each other.
+pub fn cyclic_pool() -> ConstantPool {
+    let mut builder = Builder::default();
+    let cyclic: PoolIndex<Type> = builder.pool.reserve();
+    let name = builder.name("ref:Cyclic");
+    builder
+        .pool
+        .put_definition(cyclic, Definition::type_(name, Type::Ref(cyclic)));
+
+    let class: PoolIndex<Class> = builder.pool.reserve();
+    let field = builder.field(class, "loop", cyclic);
+    let method = builder.function(
+        class,
+        "GetLoop;Cyclic",
+        FunctionFlags::new(),
+        Some(cyclic),
+        &[("other", cyclic, ParameterFlags::new())],
+        vec![Instr::Return, Instr::ObjectField(field)],
+        None,
+    );
+    let get_self = builder.function(
+        class,
+        "GetSelf;",
+        FunctionFlags::new(),
+        Some(class.cast()),
+        &[],
+        vec![Instr::Return],
+        None,
+    );
+    builder.class(
+        class,
+        "CyclicClass",
+        PoolIndex::UNDEFINED,
+        vec![method, get_self],
+        vec![field],
+    );
+
+    let first: PoolIndex<Class> = builder.pool.reserve();
+    let second: PoolIndex<Class> = builder.pool.reserve();
+    builder.class(first, "CycleFirst", second, vec![], vec![]);
+    builder.class(second, "CycleSecond", first, vec![], vec![]);
+    builder.pool
+}
+
+#[derive(Default)]
+struct Builder {
+    pool: ConstantPool,
+}
+
+impl Builder {
+    fn name(&mut self, name: &str) -> PoolIndex<CName> {
+        self.pool.names.add(name.into())
+    }
+
+    fn prim(&mut self, name: &str) -> PoolIndex<Type> {
+        self.type_(name, Type::Prim)
+    }
+
+    fn type_(&mut self, name: &str, type_: Type) -> PoolIndex<Type> {
+        let name = self.name(name);
+        self.pool.add_definition(Definition::type_(name, type_))
+    }
+
+    fn enum_(&mut self, name: &str, members: &[(&str, i64)]) -> PoolIndex<Enum> {
+        let enum_: PoolIndex<Enum> = self.pool.reserve();
+        let members = members
+            .iter()
+            .map(|(member, value)| {
+                let member = self.name(member);
+                self.pool.add_definition(Definition::enum_value(member, enum_, *value))
+            })
+            .collect();
+        let name = self.name(name);
+        let definition = Definition::enum_(
+            name,
+            Enum {
+                flags: 0,
+                size: 4,
+                members,
+                unk1: false,
+            },
+        );
+        self.pool.put_definition(enum_, definition);
+        enum_
+    }
+
+    fn class(
+        &mut self,
+        class: PoolIndex<Class>,
+        name: &str,
+        base: PoolIndex<Class>,
+        functions: Vec<PoolIndex<Function>>,
+        fields: Vec<PoolIndex<Field>>,
+    ) {
+        let name = self.name(name);
+        let definition = Definition::class(
+            name,
+            Class {
+                visibility: Visibility::Public,
+                flags: ClassFlags::new(),
+                base,
+                functions,
+                fields,
+                overrides: vec![],
+            },
+        );
+        self.pool.put_definition(class, definition);
+    }
+
+    fn field(&mut self, class: PoolIndex<Class>, name: &str, type_: PoolIndex<Type>) -> PoolIndex<Field> {
+        self.field_with(class, name, type_, FieldFlags::new())
+    }
+
+    fn field_with(
+        &mut self,
+        class: PoolIndex<Class>,
+        name: &str,
+        type_: PoolIndex<Type>,
+        flags: FieldFlags,
+    ) -> PoolIndex<Field> {
+        let name = self.name(name);
+        let field = Field {
+            visibility: Visibility::Public,
+            type_,
+            flags,
+            hint: None,
+            attributes: vec![],
+            defaults: vec![],
+        };
+        self.pool.add_definition(Definition::field(name, class, field))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn function(
+        &mut self,
+        parent: PoolIndex<Class>,
+        name: &str,
+        flags: FunctionFlags,
+        return_type: Option<PoolIndex<Type>>,
+        params: &[(&str, PoolIndex<Type>, ParameterFlags)],
+        code: Vec<Instr<Offset>>,
+        base_method: Option<PoolIndex<Function>>,
+    ) -> PoolIndex<Function> {
+        let fun: PoolIndex<Function> = self.pool.reserve();
+        let parameters = params
+            .iter()
+            .map(|(param, type_, flags)| {
+                let param = self.name(param);
+                let parameter = Parameter {
+                    type_: *type_,
+                    flags: *flags,
+                };
+                self.pool.add_definition(Definition::param(param, fun, parameter))
+            })
+            .collect();
+        let name = self.name(name);
+        let function = Function {
+            visibility: Visibility::Public,
+            flags,
+            source: None,
+            return_type,
+            unk1: false,
+            base_method,
+            parameters,
+            locals: vec![],
+            operator: None,
+            cast: 0,
+            code: Code(code),
+            unk2: vec![],
+        };
+        self.pool
+            .put_definition(fun, Definition::function(name, parent, function));
+        fun
+    }
+}
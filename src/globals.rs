@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use redscript::bundle::{ConstantPool, PoolIndex};
+use redscript::definition::{AnyDefinition, Definition};
+use serde_json::{json, Value};
+
+use crate::render::unmangled;
+
+const MATH_HELPERS: &[&str] = &[
+    "Abs",
+    "AbsF",
+    "ACos",
+    "ASin",
+    "ATan",
+    "ATan2",
+    "Ceil",
+    "CeilF",
+    "Clamp",
+    "ClampF",
+    "Cos",
+    "Exp",
+    "Floor",
+    "FloorF",
+    "FMod",
+    "Lerp",
+    "LerpF",
+    "Max",
+    "MaxF",
+    "Min",
+    "MinF",
+    "Pow",
+    "PowF",
+    "Rand",
+    "RandF",
+    "RandRange",
+    "RandRangeF",
+    "Round",
+    "RoundF",
+    "Sign",
+    "SignF",
+    "Sin",
+    "Sqrt",
+    "SqrtF",
+    "Tan",
+];
+
+/// Groups global functions by the source file they are defined in and by a theme derived
+/// from their name (`Log`, `Operator`, `Cast`, `Math` or the leading word of the name).
+pub fn group_globals(pool: &ConstantPool, roots: &[(PoolIndex<Definition>, &Definition)]) -> anyhow::Result<Value> {
+    let mut by_source: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    let mut by_theme: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    for &(idx, def) in roots {
+        let AnyDefinition::Function(fun) = &def.value else {
+            continue;
+        };
+        let name = pool.names.get(def.name)?;
+        let file = fun
+            .source
+            .as_ref()
+            .map(|source| pool.definition(source.file))
+            .transpose()?;
+        let source = match file.map(|def| &def.value) {
+            Some(AnyDefinition::SourceFile(file)) => file.path.display().to_string(),
+            _ => "<native>".to_owned(),
+        };
+        let entry = json!({"name": name.as_ref(), "index": u32::from(idx)});
+        by_source.entry(source).or_default().push(entry.clone());
+        by_theme.entry(theme(&name).to_owned()).or_default().push(entry);
+    }
+    Ok(json!({"bySource": by_source, "byTheme": by_theme}))
+}
+
+fn theme(name: &str) -> &str {
+    let name = unmangled(name);
+    if MATH_HELPERS.contains(&name) {
+        return "Math";
+    }
+    for prefix in ["Operator", "Cast", "Log"] {
+        if name.starts_with(prefix) {
+            return prefix;
+        }
+    }
+    let end = name
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| c.is_uppercase())
+        .map_or(name.len(), |(i, _)| i);
+    &name[..end]
+}
@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+
+use redscript::bundle::{ConstantPool, PoolIndex};
+use redscript::definition::AnyDefinition;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+// number of hex digits of the SHA-256 digest kept in a hash
+const HASH_LENGTH: usize = 16;
+
+/// Maps a hash of every function signature to the functions having it, with their current pool
+/// index and a slug.
+///
+/// The signature is the owner name, the full function name (which encodes parameter types) and
+/// the return type. It doesn't depend on pool indexes, so a hash stays the same across versions
+/// as long as the signature does, and overloads get distinct hashes. Functions with the same
+/// signature, as found with duplicated definitions, are all listed under their shared hash.
+pub fn function_hashes(pool: &ConstantPool) -> anyhow::Result<BTreeMap<String, Vec<Value>>> {
+    let mut hashes: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    for (idx, def) in pool.definitions() {
+        let AnyDefinition::Function(fun) = &def.value else {
+            continue;
+        };
+        let name = pool.names.get(def.name)?;
+        let qualified = if def.parent == PoolIndex::UNDEFINED {
+            name.to_string()
+        } else {
+            format!("{}::{}", pool.def_name(def.parent)?, name)
+        };
+        let signature = match fun.return_type {
+            Some(ret) => format!("{} -> {}", qualified, pool.def_name(ret)?),
+            None => qualified.clone(),
+        };
+        hashes.entry(hash(&signature)).or_default().push(json!({
+            "index": u32::from(idx),
+            "slug": slug(&qualified),
+            "signature": signature,
+        }));
+    }
+    Ok(hashes)
+}
+
+fn hash(signature: &str) -> String {
+    let mut hash = hex::encode(Sha256::digest(signature.as_bytes()));
+    hash.truncate(HASH_LENGTH);
+    hash
+}
+
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_owned()
+}
@@ -0,0 +1,223 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection};
+use serde_json::{json, Value};
+
+use crate::diff::Snapshot;
+use crate::render::unmangled;
+
+const CHURN_REPORT_LIMIT: usize = 50;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS versions (
+    id INTEGER PRIMARY KEY,
+    label TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS events (
+    version_id INTEGER NOT NULL REFERENCES versions(id),
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    event TEXT NOT NULL,
+    signature TEXT
+);
+CREATE INDEX IF NOT EXISTS events_by_name ON events(name);
+CREATE TABLE IF NOT EXISTS symbols (
+    name TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    signature TEXT NOT NULL
+);
+";
+
+/// An append-only SQLite database recording when symbols appeared, changed and disappeared.
+///
+/// The `events` table is the history itself, `symbols` only caches the latest known state
+/// to diff new runs against.
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn record(&mut self, version: &str, snapshot: &Snapshot) -> anyhow::Result<()> {
+        let tx = self.conn.transaction()?;
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        tx.execute(
+            "INSERT INTO versions (label, created_at) VALUES (?1, ?2)",
+            params![version, created_at],
+        )?;
+        let version_id = tx.last_insert_rowid();
+
+        let mut known: HashMap<String, (String, String)> = {
+            let mut stmt = tx.prepare("SELECT name, kind, signature FROM symbols")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?;
+            rows.collect::<Result<_, _>>()?
+        };
+
+        for (name, kind, signature) in snapshot.signatures() {
+            let event = match known.remove(&name) {
+                None => "added",
+                Some((_, old)) if old != signature => "changed",
+                Some(_) => continue,
+            };
+            tx.execute(
+                "INSERT INTO events (version_id, name, kind, event, signature) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![version_id, name, kind, event, signature],
+            )?;
+            tx.execute(
+                "INSERT INTO symbols (name, kind, signature) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(name) DO UPDATE SET kind = excluded.kind, signature = excluded.signature",
+                params![name, kind, signature],
+            )?;
+        }
+
+        for (name, (kind, _)) in known {
+            tx.execute(
+                "INSERT INTO events (version_id, name, kind, event, signature) VALUES (?1, ?2, ?3, 'removed', NULL)",
+                params![version_id, name, kind],
+            )?;
+            tx.execute("DELETE FROM symbols WHERE name = ?1", params![name])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Counts how often each root definition changed, itself or through its members, ranked
+    /// from the least stable. Events of the first recorded version are skipped, since
+    /// everything is added there.
+    pub fn churn(&self) -> anyhow::Result<Churn> {
+        let mut stmt = self.conn.prepare(
+            "SELECT e.name, e.event, v.id, v.label FROM events e JOIN versions v ON v.id = e.version_id
+             WHERE v.id > (SELECT MIN(id) FROM versions) ORDER BY v.id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+
+        let mut entries: BTreeMap<String, ChurnEntry> = BTreeMap::new();
+        for row in rows {
+            let (name, event, version_id, label) = row?;
+            let owner = name.split_once("::").map_or(name.as_str(), |(owner, _)| owner);
+            let entry = entries.entry(owner.to_owned()).or_default();
+            if entry.versions.last() != Some(&version_id) {
+                entry.versions.push(version_id);
+            }
+            match event.as_str() {
+                "added" => entry.added += 1,
+                "changed" => entry.changed += 1,
+                _ => entry.removed += 1,
+            }
+            entry.last_version = label;
+        }
+        let versions: u32 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM versions", [], |row| row.get(0))?;
+        // the first version only adds symbols, so it doesn't count as a change
+        let versions = versions.saturating_sub(1);
+
+        let mut entries: Vec<(String, ChurnEntry)> = entries.into_iter().collect();
+        entries.sort_by(|(a_name, a), (b_name, b)| {
+            (b.versions.len(), b.events())
+                .cmp(&(a.versions.len(), a.events()))
+                .then_with(|| a_name.cmp(b_name))
+        });
+        Ok(Churn { versions, entries })
+    }
+}
+
+/// Per-definition change counts across the recorded versions.
+pub struct Churn {
+    versions: u32,
+    entries: Vec<(String, ChurnEntry)>,
+}
+
+impl Churn {
+    /// Number of versions recorded after the first one.
+    pub fn versions(&self) -> u32 {
+        self.versions
+    }
+
+    /// Yields the number of versions each definition changed in.
+    pub fn changes(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.entries
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry.versions.len()))
+    }
+
+    pub fn to_json(&self) -> Value {
+        let entries: Vec<Value> = self
+            .entries
+            .iter()
+            .map(|(name, entry)| {
+                json!({
+                    "name": name,
+                    "versionsChanged": entry.versions.len(),
+                    "added": entry.added,
+                    "changed": entry.changed,
+                    "removed": entry.removed,
+                    "lastChangedIn": entry.last_version,
+                })
+            })
+            .collect();
+        json!({"versions": self.versions, "entries": entries})
+    }
+
+    pub fn render_markdown(&self) -> anyhow::Result<String> {
+        let mut out = String::from("# API churn\n");
+        if self.entries.is_empty() {
+            writeln!(out, "\nNothing changed since the first recorded version.")?;
+            return Ok(out);
+        }
+        writeln!(
+            out,
+            "\nDefinitions that changed most often across {} versions.\n",
+            self.versions
+        )?;
+        writeln!(
+            out,
+            "| Name | Versions changed | Added | Changed | Removed | Last changed in |"
+        )?;
+        writeln!(out, "| --- | --- | --- | --- | --- | --- |")?;
+        for (name, entry) in self.entries.iter().take(CHURN_REPORT_LIMIT) {
+            writeln!(
+                out,
+                "| `{}` | {} | {} | {} | {} | {} |",
+                unmangled(name),
+                entry.versions.len(),
+                entry.added,
+                entry.changed,
+                entry.removed,
+                entry.last_version
+            )?;
+        }
+        Ok(out)
+    }
+}
+
+#[derive(Default)]
+struct ChurnEntry {
+    versions: Vec<i64>,
+    added: u32,
+    changed: u32,
+    removed: u32,
+    last_version: String,
+}
+
+impl ChurnEntry {
+    fn events(&self) -> u32 {
+        self.added + self.changed + self.removed
+    }
+}
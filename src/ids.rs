@@ -0,0 +1,94 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use redscript::bundle::ConstantPool;
+use redscript::definition::AnyDefinition;
+use serde_json::Value;
+
+use crate::diff::read_json;
+
+/// Numeric symbol IDs persisted across runs, so that they survive pool index reshuffles.
+///
+/// Root definitions are keyed by their name and members by `Owner::member`.
+pub struct StableIds {
+    ids: BTreeMap<String, u64>,
+    next: u64,
+}
+
+impl StableIds {
+    /// Loads a mapping file, starting from an empty mapping if it does not exist yet.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let ids: BTreeMap<String, u64> = if path.exists() {
+            read_json(path)?
+                .as_object()
+                .into_iter()
+                .flatten()
+                .filter_map(|(key, id)| Some((key.clone(), id.as_u64()?)))
+                .collect()
+        } else {
+            BTreeMap::new()
+        };
+        let next = ids.values().max().map_or(1, |max| max + 1);
+        Ok(Self { ids, next })
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(&self.ids)?)?;
+        Ok(())
+    }
+
+    /// Assigns IDs to every documented symbol of the pool that does not have one yet.
+    pub fn assign(&mut self, pool: &ConstantPool) -> anyhow::Result<()> {
+        for (_, def) in pool.roots() {
+            let members = match &def.value {
+                AnyDefinition::Class(class) => class
+                    .fields
+                    .iter()
+                    .map(|idx| pool.def_name(*idx))
+                    .chain(class.functions.iter().map(|idx| pool.def_name(*idx)))
+                    .collect::<Result<Vec<_>, _>>()?,
+                AnyDefinition::Enum(enum_) => enum_
+                    .members
+                    .iter()
+                    .map(|idx| pool.def_name(*idx))
+                    .collect::<Result<Vec<_>, _>>()?,
+                AnyDefinition::Function(_) => vec![],
+                _ => continue,
+            };
+            let name = pool.names.get(def.name)?;
+            self.insert(name.to_string());
+            for member in members {
+                self.insert(format!("{}::{}", name, member));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Option<u64> {
+        self.ids.get(key).copied()
+    }
+
+    /// Adds `stableId` to an encoded root document and its members.
+    pub fn annotate(&self, doc: &mut Value) {
+        let Some(name) = doc["name"].as_str().map(str::to_owned) else {
+            return;
+        };
+        doc["stableId"] = self.get(&name).into();
+        for key in ["fields", "methods", "members"] {
+            for member in doc.get_mut(key).and_then(Value::as_array_mut).into_iter().flatten() {
+                if let Some(member_name) = member["name"].as_str() {
+                    let id = self.get(&format!("{}::{}", name, member_name));
+                    member["stableId"] = id.into();
+                }
+            }
+        }
+    }
+
+    fn insert(&mut self, key: String) {
+        if let Entry::Vacant(entry) = self.ids.entry(key) {
+            entry.insert(self.next);
+            self.next += 1;
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use crate::render::unmangled;
+
+/// Symbol patterns read from an ignore file.
+///
+/// Each line holds a pattern matched against definition names, where `*` matches any run of
+/// characters and `?` a single one. Patterns starting with `!` force-include the definitions
+/// they match. Later lines take precedence over earlier ones, blank lines and lines starting
+/// with `#` are skipped.
+pub struct IgnoreList {
+    rules: Vec<(String, bool)>,
+}
+
+impl IgnoreList {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| match line.strip_prefix('!') {
+                Some(pattern) => (pattern.to_owned(), true),
+                None => (line.to_owned(), false),
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Returns whether the last pattern matching the name includes it, or `None` if no
+    /// pattern matches. Names are matched both with and without their signature suffix.
+    pub fn verdict(&self, name: &str) -> Option<bool> {
+        let pretty = unmangled(name);
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| {
+                glob_match(pattern.as_bytes(), name.as_bytes()) || glob_match(pattern.as_bytes(), pretty.as_bytes())
+            })
+            .map(|(_, include)| *include)
+    }
+}
+
+fn glob_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => glob_match(&pattern[1..], name) || (!name.is_empty() && glob_match(pattern, &name[1..])),
+        (Some(b'?'), Some(_)) => glob_match(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => glob_match(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
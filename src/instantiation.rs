@@ -0,0 +1,93 @@
+use std::collections::{HashMap, HashSet};
+
+use redscript::bundle::{ConstantPool, PoolIndex};
+use redscript::bytecode::Instr;
+use redscript::definition::{AnyDefinition, Class, Definition};
+use serde_json::{json, Value};
+
+use crate::encode_owned_ref;
+use crate::usage::{resolve_type, type_lookup};
+
+pub const EVENT_BASES: &[&str] = &["Event", "redEvent"];
+const MAX_LISTED_GETTERS: usize = 5;
+
+/// Records how scripts obtain instances of each class: the constructions found in bytecode and the
+/// getter-like functions returning the class.
+pub struct InstantiationIndex {
+    constructions: HashMap<PoolIndex<Definition>, u32>,
+    getters: HashMap<PoolIndex<Definition>, Vec<PoolIndex<Definition>>>,
+}
+
+impl InstantiationIndex {
+    pub fn build(pool: &ConstantPool) -> anyhow::Result<Self> {
+        let types = type_lookup(pool);
+        let mut constructions: HashMap<PoolIndex<Definition>, u32> = HashMap::new();
+        let mut getters: HashMap<PoolIndex<Definition>, Vec<PoolIndex<Definition>>> = HashMap::new();
+        for (idx, def) in pool.definitions() {
+            let AnyDefinition::Function(fun) = &def.value else {
+                continue;
+            };
+            for instr in &fun.code.0 {
+                if let Instr::New(class) | Instr::Construct(_, class) = instr {
+                    *constructions.entry(class.cast()).or_default() += 1;
+                }
+            }
+            if let Some(ret) = fun.return_type {
+                if pool.names.get(def.name)?.starts_with("Get") {
+                    if let Some(class) = resolve_type(ret, &types, pool)? {
+                        getters.entry(class).or_default().push(idx);
+                    }
+                }
+            }
+        }
+        Ok(Self { constructions, getters })
+    }
+
+    /// Describes how a class is typically instantiated: `new` when scripts construct it, `getter`
+    /// when it's only returned by getters, `event` for events the engine sends, `abstract`, or
+    /// `unknown`.
+    pub fn hint(&self, idx: PoolIndex<Definition>, class: &Class, pool: &ConstantPool) -> anyhow::Result<Value> {
+        let constructions = self.constructions.get(&idx).copied().unwrap_or(0);
+        let getters = self.getters.get(&idx).map(Vec::as_slice).unwrap_or_default();
+        let kind = if class.flags.is_abstract() {
+            "abstract"
+        } else if constructions > 0 {
+            "new"
+        } else if !getters.is_empty() {
+            "getter"
+        } else if is_event(class, pool)? {
+            "event"
+        } else {
+            "unknown"
+        };
+        let listed: anyhow::Result<Vec<Value>> = getters
+            .iter()
+            .take(MAX_LISTED_GETTERS)
+            .map(|getter| encode_owned_ref(*getter, pool))
+            .collect();
+        Ok(json!({
+            "kind": kind,
+            "constructions": constructions,
+            "getters": listed?,
+            "getterCount": getters.len(),
+        }))
+    }
+}
+
+fn is_event(class: &Class, pool: &ConstantPool) -> anyhow::Result<bool> {
+    derives_from(class, EVENT_BASES, pool)
+}
+
+/// Returns true if any of the transitive bases of a class has one of the given names. A cyclic
+/// base chain is followed until its first repeated class.
+pub fn derives_from(class: &Class, bases: &[&str], pool: &ConstantPool) -> anyhow::Result<bool> {
+    let mut visited = HashSet::new();
+    let mut base = class.base;
+    while base != PoolIndex::UNDEFINED && visited.insert(base) {
+        if bases.contains(&pool.def_name(base)?.as_ref()) {
+            return Ok(true);
+        }
+        base = pool.class(base)?.base;
+    }
+    Ok(false)
+}
@@ -0,0 +1,36 @@
+use serde_json::{json, Value};
+
+use crate::render::render_type;
+
+/// Collects the layout-relevant data of an encoded struct document: the length of its base
+/// chain and its fields in declaration order, with native offsets where they're known.
+pub fn struct_layout(doc: &Value) -> Option<Value> {
+    if doc["isStruct"] != true {
+        return None;
+    }
+    let mut fields: Vec<&Value> = doc["fields"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|field| field["ordinal"].is_u64())
+        .collect();
+    fields.sort_by_key(|field| field["ordinal"].as_u64());
+    let fields: Vec<Value> = fields
+        .into_iter()
+        .map(|field| {
+            json!({
+                "name": field["name"],
+                "ordinal": field["ordinal"],
+                "type": render_type(&field["type"]),
+                "offset": field.get("offset"),
+            })
+        })
+        .collect();
+    Some(json!({
+        "isStruct": true,
+        "isNative": doc["isNative"],
+        "baseChainLength": doc["bases"].as_array().map_or(0, Vec::len),
+        "fieldCount": fields.len(),
+        "fields": fields,
+    }))
+}
@@ -0,0 +1,500 @@
+pub mod advisor;
+pub mod analysis;
+pub mod dependencies;
+pub mod diff;
+pub mod document;
+pub mod embed;
+pub mod enums;
+#[cfg(any(test, feature = "bench"))]
+pub mod fixture;
+pub mod globals;
+pub mod hashes;
+pub mod history;
+pub mod ids;
+pub mod ignore;
+pub mod instantiation;
+pub mod layout;
+pub mod lint;
+pub mod loader;
+pub mod localization;
+pub mod manifest;
+pub mod members;
+pub mod native_db;
+pub mod nested;
+pub mod pipeline;
+pub mod recovery;
+pub mod render;
+pub mod scoped;
+pub mod signing;
+pub mod stability;
+pub mod stats;
+pub mod subtree;
+pub mod symbol_graph;
+pub mod system_map;
+#[cfg(test)]
+mod tests;
+pub mod usage;
+pub mod webhook;
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use redscript::bundle::{CName, ConstantPool, PoolIndex};
+use redscript::definition::{AnyDefinition, Class, Definition, Type};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::{json, Value};
+
+use crate::analysis::Analysis;
+use crate::ids::StableIds;
+use crate::recovery::Recovery;
+use crate::stability::Stability;
+use crate::usage::UsageIndex;
+
+pub fn encode_definition(definition: &Definition, pool: &ConstantPool, analysis: &Analysis) -> anyhow::Result<Value> {
+    let result = match &definition.value {
+        AnyDefinition::Type(type_) => encode_type(definition, type_, pool, analysis, &mut vec![])?,
+        AnyDefinition::Class(class) => {
+            let fields: anyhow::Result<Vec<Value>> = class
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(ordinal, f)| {
+                    let mut field = encode_member(*f, pool, analysis)?;
+                    field["ordinal"] = ordinal.into();
+                    Ok(field)
+                })
+                .collect();
+            let methods: anyhow::Result<Vec<Value>> = class
+                .functions
+                .iter()
+                .map(|f| encode_member(*f, pool, analysis))
+                .collect();
+            json!({
+                "tag": "Class",
+                "name": pool.names.get(definition.name)?.as_ref(),
+                "visibility": format!("{}", class.visibility).to_lowercase(),
+                "bases": collect_bases(class.base, pool)?,
+                "fields": fields?,
+                "methods": methods?,
+                "isNative": class.flags.is_native(),
+                "isAbstract": class.flags.is_abstract(),
+                "isFinal": class.flags.is_final(),
+                "isStruct": class.flags.is_struct(),
+            })
+        }
+        AnyDefinition::EnumValue(val) => json!({
+            "tag": "EnumValue",
+            "name": pool.names.get(definition.name)?.as_ref(),
+            "value": val,
+        }),
+        AnyDefinition::Enum(enum_) => {
+            let members: anyhow::Result<Vec<Value>> = enum_
+                .members
+                .iter()
+                .map(|m| encode_member(*m, pool, analysis))
+                .collect();
+            json!({
+                "tag": "Enum",
+                "name": pool.names.get(definition.name)?.as_ref(),
+                "members": members?
+            })
+        }
+        AnyDefinition::Function(fun) => {
+            let parameters: anyhow::Result<Vec<Value>> = fun
+                .parameters
+                .iter()
+                .map(|m| encode_definition(pool.definition(*m)?, pool, analysis))
+                .collect();
+            let accessor_of = match analysis::accessed_field(fun) {
+                Some(field) => Some(json!({"name": pool.def_name(field)?.as_ref(), "index": u32::from(field)})),
+                None => None,
+            };
+            let return_enum = match fun.return_type {
+                Some(type_) => inline_enum(type_, pool, analysis)?,
+                None => None,
+            };
+            let return_type = fun
+                .return_type
+                .map(|idx| encode_inner_type(idx, pool, analysis, &mut vec![]))
+                .transpose()?;
+            let source = match &fun.source {
+                Some(source) => match &pool.definition(source.file)?.value {
+                    AnyDefinition::SourceFile(file) => Some(file.path.display().to_string()),
+                    _ => bail!("definition {} is not a source file", u32::from(source.file)),
+                },
+                None => None,
+            };
+            json!({
+                "tag": "Function",
+                "name": pool.names.get(definition.name)?.as_ref(),
+                "parameters": parameters?,
+                "returnType": return_type,
+                "returnTypeIndex": fun.return_type.map(u32::from),
+                "returnEnum": return_enum,
+                "visibility": format!("{}", fun.visibility).to_lowercase(),
+                "isStatic": fun.flags.is_static(),
+                "isFinal": fun.flags.is_final(),
+                "isExec": fun.flags.is_exec(),
+                "isCallback": fun.flags.is_callback(),
+                "isNative": fun.flags.is_native(),
+                "isStub": analysis::is_stub(fun),
+                "behaviorHints": analysis::behavior_hints(fun, pool)?,
+                "accessorOf": accessor_of,
+                "source": source
+            })
+        }
+        AnyDefinition::Parameter(param) => json!({
+            "tag": "Parameter",
+            "name": pool.names.get(definition.name)?.as_ref(),
+            "type": encode_inner_type(param.type_, pool, analysis, &mut vec![])?,
+            "typeIndex": u32::from(param.type_),
+            "enum": inline_enum(param.type_, pool, analysis)?,
+            "isOut": param.flags.is_out(),
+            "isOptional": param.flags.is_optional(),
+            "defaultValue": if param.flags.is_optional() {
+                analysis::implicit_default(param.type_, analysis.types(), pool)?
+            } else {
+                None
+            },
+        }),
+        AnyDefinition::Field(field) => json!({
+            "tag": "Field",
+            "name": pool.names.get(definition.name)?.as_ref(),
+            "type": encode_inner_type(field.type_, pool, analysis, &mut vec![])?,
+            "typeIndex": u32::from(field.type_),
+            "isNative": field.flags.is_native(),
+            "isEdit": field.flags.is_editable(),
+            "isInline": field.flags.is_inline(),
+            "isConst": field.flags.is_const(),
+            "isRep": field.flags.is_replicated(),
+            "isPersistent": field.flags.is_persistent(),
+        }),
+        AnyDefinition::SourceFile(f) => Value::String(f.path.display().to_string()),
+        AnyDefinition::Local(_) => bail!("local {} can't be encoded", pool.names.get(definition.name)?),
+    };
+    Ok(result)
+}
+
+/// Encodes a type, failing if it refers to itself or nests deeper than the analysis allows.
+fn encode_type(
+    definition: &Definition,
+    type_: &Type,
+    pool: &ConstantPool,
+    analysis: &Analysis,
+    path: &mut Vec<PoolIndex<Type>>,
+) -> anyhow::Result<Value> {
+    let result = match type_ {
+        Type::Prim => json!({"tag": "Type", "kind": "Prim", "name": pool.names.get(definition.name)?.as_ref()}),
+        Type::Class => {
+            let name = pool.names.get(definition.name)?;
+            let class = find_type(definition.name, analysis).ok_or_else(|| anyhow!("type {} is not defined", name))?;
+            let class_idx: u32 = class.into();
+            json!({"tag": "Type", "kind": "Class", "name": name.as_ref(), "index": class_idx })
+        }
+        Type::Ref(inner) => {
+            json!({"tag": "Type", "kind": "Ref", "inner": encode_inner_type(*inner, pool, analysis, path)?})
+        }
+        Type::WeakRef(inner) => {
+            json!({"tag": "Type", "kind": "WeakRef", "inner": encode_inner_type(*inner, pool, analysis, path)?})
+        }
+        Type::ScriptRef(inner) => {
+            json!({"tag": "Type", "kind": "ScriptRef", "inner": encode_inner_type(*inner, pool, analysis, path)?})
+        }
+        Type::Array(inner) => {
+            json!({"tag": "Type", "kind": "Array", "inner": encode_inner_type(*inner, pool, analysis, path)?})
+        }
+        Type::StaticArray(inner, size) => {
+            json!({"tag": "Type", "kind": "StaticArray", "size": size, "inner": encode_inner_type(*inner, pool, analysis, path)?})
+        }
+    };
+    Ok(result)
+}
+
+fn encode_inner_type(
+    idx: PoolIndex<Type>,
+    pool: &ConstantPool,
+    analysis: &Analysis,
+    path: &mut Vec<PoolIndex<Type>>,
+) -> anyhow::Result<Value> {
+    if path.contains(&idx) {
+        bail!("type {} refers to itself", u32::from(idx));
+    }
+    if path.len() >= analysis.max_type_depth() {
+        bail!(
+            "type {} is nested deeper than {} levels",
+            u32::from(idx),
+            analysis.max_type_depth()
+        );
+    }
+    let def = pool.definition(idx)?;
+    let AnyDefinition::Type(type_) = &def.value else {
+        bail!("definition {} is not a type", u32::from(idx));
+    };
+    path.push(idx);
+    let encoded = encode_type(def, type_, pool, analysis, path)?;
+    path.pop();
+    Ok(encoded)
+}
+
+fn encode_member<A>(idx: PoolIndex<A>, pool: &ConstantPool, analysis: &Analysis) -> anyhow::Result<Value> {
+    let mut encoded = encode_definition(pool.definition(idx)?, pool, analysis)?;
+    encoded["usageCount"] = analysis.usages.count(idx).into();
+    if let Some(implementations) = analysis.implementations(idx) {
+        let implementations: anyhow::Result<Vec<Value>> =
+            implementations.iter().map(|idx| encode_owned_ref(*idx, pool)).collect();
+        encoded["implementations"] = implementations?.into();
+    }
+    Ok(encoded)
+}
+
+/// Encodes a reference to a definition along with the definition that owns it, if any.
+pub fn encode_owned_ref(idx: PoolIndex<Definition>, pool: &ConstantPool) -> anyhow::Result<Value> {
+    let def = pool.definition(idx)?;
+    let owner = if def.parent == PoolIndex::UNDEFINED {
+        None
+    } else {
+        Some(json!({"name": pool.def_name(def.parent)?.as_ref(), "index": u32::from(def.parent)}))
+    };
+    Ok(json!({
+        "name": pool.names.get(def.name)?.as_ref(),
+        "index": u32::from(idx),
+        "owner": owner,
+    }))
+}
+
+/// Summarizes the enum a type refers to, so that it can be shown inline with the parameter or return type.
+fn inline_enum(type_: PoolIndex<Type>, pool: &ConstantPool, analysis: &Analysis) -> anyhow::Result<Option<Value>> {
+    let def = pool.definition(type_)?;
+    if !matches!(&def.value, AnyDefinition::Type(Type::Class)) {
+        return Ok(None);
+    }
+    let Some(idx) = find_type(def.name, analysis) else {
+        return Ok(None);
+    };
+    let AnyDefinition::Enum(enum_) = &pool.definition(idx)?.value else {
+        return Ok(None);
+    };
+    let members: anyhow::Result<Vec<Value>> = enum_
+        .members
+        .iter()
+        .map(|member| {
+            let def = pool.definition(*member)?;
+            Ok(json!({"name": pool.names.get(def.name)?.as_ref(), "value": def.value.as_enum_value()}))
+        })
+        .collect();
+    let members = members?;
+    Ok(Some(json!({
+        "index": u32::from(idx),
+        "memberCount": members.len(),
+        "members": members,
+    })))
+}
+
+fn find_type(name: PoolIndex<CName>, analysis: &Analysis) -> Option<PoolIndex<Class>> {
+    analysis.types().get(&name).map(|idx| idx.cast())
+}
+
+pub fn is_documented(def: &Definition) -> bool {
+    matches!(&def.value, AnyDefinition::Class(_))
+        || matches!(&def.value, AnyDefinition::Function(_))
+        || matches!(&def.value, AnyDefinition::Enum(_))
+}
+
+pub fn build_index(
+    pool: &ConstantPool,
+    roots: &[(PoolIndex<Definition>, &Definition)],
+    usages: &UsageIndex,
+    ids: Option<&StableIds>,
+    stability: Option<&Stability>,
+    function_stats: bool,
+) -> Vec<Reference> {
+    roots
+        .iter()
+        .map(|&(index, def)| {
+            let name = pool.names.get(def.name).unwrap();
+            let pretty = Arc::from(render::unmangled(&name));
+            let base = def.value.as_class().map(|c| c.base.cast());
+            let member_counts = def.value.as_class().map(|class| MemberCounts {
+                fields: class.fields.len(),
+                methods: class.functions.len(),
+                overrides: class
+                    .functions
+                    .iter()
+                    .filter(|idx| pool.function(**idx).is_ok_and(|fun| fun.base_method.is_some()))
+                    .count(),
+            });
+            let function_stats = def
+                .value
+                .as_function()
+                .filter(|_| function_stats)
+                .map(|fun| FunctionStats {
+                    parameters: fun.parameters.len(),
+                    locals: fun.locals.len(),
+                    code_size: fun.code.0.iter().map(|instr| usize::from(instr.size())).sum(),
+                });
+            let usage_count = usages.count(index);
+            Reference {
+                name: pretty,
+                index,
+                base,
+                usage_count: Some(usage_count),
+                stable_id: ids.and_then(|ids| ids.get(&name)),
+                stability_score: stability.map(|stability| stability.assess(&name, def, usage_count).score),
+                member_counts,
+                function_stats,
+            }
+        })
+        .collect()
+}
+
+/// Writes the index split into one file per first letter of the names, along with a small
+/// manifest listing the files, so that clients can load only the part they need.
+pub fn write_split_index(output: &Path, index: &[Reference]) -> anyhow::Result<()> {
+    let mut parts: BTreeMap<String, Vec<&Reference>> = BTreeMap::new();
+    for reference in index {
+        let key = match reference.name.chars().next() {
+            Some(c) if c.is_ascii_alphabetic() => c.to_ascii_uppercase().to_string(),
+            _ => "_".to_owned(),
+        };
+        parts.entry(key).or_default().push(reference);
+    }
+
+    let dir = output.join("index");
+    std::fs::create_dir_all(&dir)?;
+    let mut files = vec![];
+    for (key, references) in &parts {
+        let file = format!("{}.json", key);
+        std::fs::write(dir.join(&file), serde_json::to_string(references)?)?;
+        files.push(json!({"key": key, "file": format!("index/{}", file), "count": references.len()}));
+    }
+    let manifest = json!({"total": index.len(), "parts": files});
+    std::fs::write(dir.join("manifest.json"), serde_json::to_string(&manifest)?)?;
+    Ok(())
+}
+
+/// Encodes every type definition of the pool keyed by its index, leaving the types that fail to
+/// encode to the recovery.
+pub fn build_types(
+    pool: &ConstantPool,
+    analysis: &Analysis,
+    recovery: &Recovery,
+) -> anyhow::Result<BTreeMap<u32, Value>> {
+    let types: Vec<(PoolIndex<Definition>, &Definition)> = pool
+        .definitions()
+        .filter(|(_, def)| matches!(&def.value, AnyDefinition::Type(_)))
+        .collect();
+    let encoded: Vec<Option<(u32, Value)>> = types
+        .par_iter()
+        .map(|&(idx, def)| {
+            let encoded = recovery.attempt(idx, encode_definition(def, pool, analysis))?;
+            Ok(encoded.map(|encoded| (u32::from(idx), encoded)))
+        })
+        .collect::<anyhow::Result<_>>()?;
+    Ok(encoded.into_iter().flatten().collect())
+}
+
+/// Lists the transitive bases of a class, stopping at the first repeated one of a cyclic chain.
+fn collect_bases(idx: PoolIndex<Class>, pool: &ConstantPool) -> anyhow::Result<Vec<Reference>> {
+    let mut bases = vec![];
+    let mut visited = HashSet::new();
+    let mut idx = idx;
+    while idx != PoolIndex::UNDEFINED && visited.insert(idx) {
+        bases.push(Reference {
+            name: pool.def_name(idx)?,
+            index: idx.cast(),
+            base: None,
+            usage_count: None,
+            stable_id: None,
+            stability_score: None,
+            member_counts: None,
+            function_stats: None,
+        });
+        idx = pool.class(idx)?.base;
+    }
+    Ok(bases)
+}
+
+pub struct Reference {
+    name: Arc<str>,
+    index: PoolIndex<Definition>,
+    base: Option<PoolIndex<Definition>>,
+    usage_count: Option<u32>,
+    stable_id: Option<u64>,
+    stability_score: Option<u8>,
+    member_counts: Option<MemberCounts>,
+    function_stats: Option<FunctionStats>,
+}
+
+impl Serialize for Reference {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Reference", 8)?;
+        state.serialize_field("name", self.name.as_ref())?;
+        state.serialize_field("index", &u32::from(self.index))?;
+        state.serialize_field("base", &self.base.map(u32::from))?;
+        match self.usage_count {
+            Some(count) => state.serialize_field("usageCount", &count)?,
+            None => state.skip_field("usageCount")?,
+        }
+        match self.stable_id {
+            Some(id) => state.serialize_field("stableId", &id)?,
+            None => state.skip_field("stableId")?,
+        }
+        match self.stability_score {
+            Some(score) => state.serialize_field("stabilityScore", &score)?,
+            None => state.skip_field("stabilityScore")?,
+        }
+        match &self.member_counts {
+            Some(counts) => state.serialize_field("memberCounts", counts)?,
+            None => state.skip_field("memberCounts")?,
+        }
+        match &self.function_stats {
+            Some(stats) => state.serialize_field("functionStats", stats)?,
+            None => state.skip_field("functionStats")?,
+        }
+        state.end()
+    }
+}
+
+pub struct MemberCounts {
+    fields: usize,
+    methods: usize,
+    overrides: usize,
+}
+
+impl Serialize for MemberCounts {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("MemberCounts", 3)?;
+        state.serialize_field("fields", &self.fields)?;
+        state.serialize_field("methods", &self.methods)?;
+        state.serialize_field("overrides", &self.overrides)?;
+        state.end()
+    }
+}
+
+pub struct FunctionStats {
+    parameters: usize,
+    locals: usize,
+    code_size: usize,
+}
+
+impl Serialize for FunctionStats {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("FunctionStats", 3)?;
+        state.serialize_field("parameters", &self.parameters)?;
+        state.serialize_field("locals", &self.locals)?;
+        state.serialize_field("codeSize", &self.code_size)?;
+        state.end()
+    }
+}
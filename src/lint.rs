@@ -0,0 +1,102 @@
+use std::collections::{BTreeMap, HashMap};
+
+use redscript::bundle::{CName, ConstantPool, PoolIndex};
+use redscript::definition::{AnyDefinition, Definition, Type};
+use serde_json::{json, Value};
+
+use crate::analysis::Analysis;
+use crate::encode_owned_ref;
+use crate::usage::{type_chain, type_lookup};
+
+/// Checks the pool for data that is likely to be broken: root definitions sharing a name, classes
+/// extending a base that isn't a class and functions returning a type that isn't defined.
+pub fn collect_warnings(pool: &ConstantPool) -> anyhow::Result<Vec<Value>> {
+    let mut warnings = vec![];
+
+    let mut by_name: BTreeMap<u32, Vec<PoolIndex<Definition>>> = BTreeMap::new();
+    for (idx, def) in pool.roots() {
+        if matches!(
+            &def.value,
+            AnyDefinition::Class(_) | AnyDefinition::Enum(_) | AnyDefinition::Function(_)
+        ) {
+            by_name.entry(u32::from(def.name)).or_default().push(idx);
+        }
+    }
+    for defs in by_name.values().filter(|defs| defs.len() > 1) {
+        for idx in defs {
+            let name = pool.def_name(*idx)?;
+            warnings.push(warning(
+                "duplicateName",
+                format!("{} is defined {} times", name, defs.len()),
+                *idx,
+                pool,
+            )?);
+        }
+    }
+
+    let types = type_lookup(pool);
+    for (idx, def) in pool.definitions() {
+        match &def.value {
+            AnyDefinition::Class(class) if class.base != PoolIndex::UNDEFINED => {
+                let is_class = pool
+                    .definition(class.base)
+                    .is_ok_and(|base| matches!(&base.value, AnyDefinition::Class(_)));
+                if !is_class {
+                    warnings.push(warning(
+                        "undefinedBase",
+                        format!(
+                            "{} extends {} which is not a defined class",
+                            pool.def_name(idx)?,
+                            u32::from(class.base)
+                        ),
+                        idx,
+                        pool,
+                    )?);
+                }
+            }
+            AnyDefinition::Function(fun) => {
+                let Some(ret) = fun.return_type else {
+                    continue;
+                };
+                if !is_defined_type(ret, &types, pool) {
+                    warnings.push(warning(
+                        "undefinedReturnType",
+                        format!(
+                            "{} returns type {} which is not defined",
+                            pool.def_name(idx)?,
+                            u32::from(ret)
+                        ),
+                        idx,
+                        pool,
+                    )?);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(warnings)
+}
+
+/// Returns true if a type definition exists and every class it refers to is defined.
+fn is_defined_type(
+    idx: PoolIndex<Type>,
+    types: &HashMap<PoolIndex<CName>, PoolIndex<Definition>>,
+    pool: &ConstantPool,
+) -> bool {
+    let Ok(chain) = type_chain(idx, pool, Analysis::DEFAULT_MAX_TYPE_DEPTH) else {
+        return false;
+    };
+    match chain.last() {
+        Some((_, def)) if matches!(&def.value, AnyDefinition::Type(Type::Class)) => types.contains_key(&def.name),
+        Some(_) => true,
+        None => false,
+    }
+}
+
+pub fn warning(kind: &str, message: String, idx: PoolIndex<Definition>, pool: &ConstantPool) -> anyhow::Result<Value> {
+    Ok(json!({
+        "kind": kind,
+        "message": message,
+        "definition": encode_owned_ref(idx, pool)?,
+    }))
+}
@@ -0,0 +1,59 @@
+use std::io::Cursor;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use redscript::bundle::ScriptBundle;
+
+/// Magic number at the start of every bundle, `REDS` in little-endian.
+const MAGIC: u32 = 0x53444552;
+/// Bundle versions that the redscript revision this is built against can decode.
+pub const SUPPORTED_VERSIONS: RangeInclusive<u32> = 12..=14;
+
+/// Reads the magic number and the version from the start of a bundle.
+pub fn probe_version(bytes: &[u8]) -> anyhow::Result<u32> {
+    let word = |offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+    };
+    let (Some(magic), Some(version)) = (word(0), word(4)) else {
+        return Err(anyhow!(
+            "file is only {} bytes long, the header is truncated",
+            bytes.len()
+        ));
+    };
+    if magic != MAGIC {
+        return Err(anyhow!("file does not start with the bundle magic number"));
+    }
+    Ok(version)
+}
+
+/// Loads a bundle after checking that its version is supported. With `best_effort` an
+/// unsupported version is only reported and decoding is attempted anyway.
+pub fn load_bundle(path: &Path, best_effort: bool) -> anyhow::Result<ScriptBundle> {
+    let bytes = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let version = probe_version(&bytes).with_context(|| format!("{} is not a valid bundle", path.display()))?;
+    if !SUPPORTED_VERSIONS.contains(&version) {
+        let message = format!(
+            "{} has bundle version {}, supported versions are {} to {}",
+            path.display(),
+            version,
+            SUPPORTED_VERSIONS.start(),
+            SUPPORTED_VERSIONS.end()
+        );
+        if !best_effort {
+            return Err(anyhow!("{}, use --best-effort to try anyway", message));
+        }
+        eprintln!("{}, continuing in best-effort mode", message);
+    }
+    ScriptBundle::load(&mut Cursor::new(&bytes)).map_err(|err| {
+        anyhow!(
+            "failed to decode {} (version {}, {} bytes), the file may be truncated or corrupt: {}",
+            path.display(),
+            version,
+            bytes.len(),
+            err
+        )
+    })
+}
@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+
+use redscript::bundle::{ConstantPool, PoolIndex};
+use redscript::bytecode::Instr;
+use redscript::definition::{AnyDefinition, Definition};
+use serde_json::{Map, Value};
+
+use crate::encode_owned_ref;
+
+const LOC_KEY_PREFIX: &str = "LocKey#";
+
+/// Builds a catalog mapping localization keys referenced from bytecode to the functions using them.
+pub fn build_catalog(pool: &ConstantPool) -> anyhow::Result<Value> {
+    let mut users: BTreeMap<String, Vec<PoolIndex<Definition>>> = BTreeMap::new();
+    for (idx, def) in pool.definitions() {
+        let AnyDefinition::Function(fun) = &def.value else {
+            continue;
+        };
+        for instr in &fun.code.0 {
+            let key = match instr {
+                Instr::StringConst(string) => pool.strings.get(*string)?,
+                Instr::NameConst(name) => pool.names.get(*name)?,
+                _ => continue,
+            };
+            if key.starts_with(LOC_KEY_PREFIX) {
+                let entry = users.entry(key.to_string()).or_default();
+                if !entry.contains(&idx) {
+                    entry.push(idx);
+                }
+            }
+        }
+    }
+
+    let mut catalog = Map::new();
+    for (key, functions) in users {
+        let functions: anyhow::Result<Vec<Value>> =
+            functions.into_iter().map(|idx| encode_owned_ref(idx, pool)).collect();
+        catalog.insert(key, Value::Array(functions?));
+    }
+    Ok(Value::Object(catalog))
+}
@@ -1,226 +1,619 @@
 use std::error::Error;
-use std::fs::File;
-use std::io::BufReader;
+use std::io::Write;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+use cyberdoc_generator::analysis::Analysis;
+use cyberdoc_generator::diff::{Diff, Snapshot};
+use cyberdoc_generator::document::Documents;
+use cyberdoc_generator::enums::EnumOrder;
+use cyberdoc_generator::history::History;
+use cyberdoc_generator::ids::StableIds;
+use cyberdoc_generator::ignore::IgnoreList;
+use cyberdoc_generator::manifest::Manifest;
+use cyberdoc_generator::native_db::NativeDb;
+use cyberdoc_generator::pipeline::PipelineConfig;
+use cyberdoc_generator::recovery::Recovery;
+use cyberdoc_generator::render::FrontMatter;
+use cyberdoc_generator::stability::Stability;
+use cyberdoc_generator::webhook::WebhookFormat;
+use cyberdoc_generator::{
+    advisor, build_index, build_types, diff, embed, globals, hashes, is_documented, lint, loader, manifest, members,
+    pipeline, render, scoped, signing, stats, subtree, symbol_graph, system_map, webhook, write_split_index,
+};
 use gumdrop::Options;
-use rayon::iter::{ParallelBridge, ParallelIterator};
-use redscript::bundle::{CName, ConstantPool, PoolIndex, ScriptBundle};
-use redscript::definition::{AnyDefinition, Class, Definition, Type};
-use serde::ser::{Serialize, SerializeStruct, Serializer};
-use serde_json::{json, Value};
+use redscript::bundle::PoolIndex;
+use redscript::definition::Definition;
+use serde_json::Value;
 
 #[derive(Debug, Options)]
 struct AppOpts {
+    #[options(command)]
+    command: Option<Command>,
+    #[options(short = "i", help = "redscript bundle file to read")]
+    input: Option<PathBuf>,
+    #[options(short = "o", help = "output directory")]
+    output: Option<PathBuf>,
+    #[options(no_short, help = "RTTI/NativeDB JSON dump to merge into class documents")]
+    native_db: Option<PathBuf>,
+    #[options(no_short, help = "previous output directory to generate a what's new page against")]
+    previous: Option<PathBuf>,
+    #[options(no_short, help = "output directory of a newer version to derive deprecations from")]
+    newer: Option<PathBuf>,
+    #[options(no_short, help = "SQLite database to record the symbol history in")]
+    history: Option<PathBuf>,
+    #[options(no_short, help = "version label to stamp history entries and notifications with")]
+    version_label: Option<String>,
+    #[options(no_short, help = "webhook URL to post a summary of the changes since --previous to")]
+    webhook: Option<String>,
+    #[options(
+        no_short,
+        default = "generic",
+        help = "webhook payload format: discord, slack or generic"
+    )]
+    webhook_format: WebhookFormat,
+    #[options(no_short, help = "JSON file persisting stable symbol IDs across runs")]
+    id_map: Option<PathBuf>,
+    #[options(no_short, help = "only document this class with its hierarchy and referenced types")]
+    root_class: Option<String>,
+    #[options(
+        no_short,
+        help = "file of symbol patterns to exclude, or force-include with a leading !"
+    )]
+    ignore_file: Option<PathBuf>,
+    #[options(
+        no_short,
+        default = "declaration",
+        help = "enum member order: declaration, value or name"
+    )]
+    enum_order: EnumOrder,
+    #[options(no_short, help = "fold enum members sharing a value into aliases")]
+    group_enum_aliases: bool,
+    #[options(no_short, default = "32", help = "maximum nesting depth of encoded types")]
+    max_type_depth: usize,
+    #[options(no_short, help = "include types used in method bodies in class dependencies")]
+    bytecode_dependencies: bool,
+    #[options(
+        no_short,
+        help = "file with a hex-encoded ed25519 secret key to sign the manifest with"
+    )]
+    signing_key: Option<PathBuf>,
+    #[options(no_short, help = "fail the run if any warnings are reported")]
+    deny_warnings: bool,
+    #[options(no_short, help = "directory to cache analysis results in, keyed by bundle hash")]
+    analysis_cache: Option<PathBuf>,
+    #[options(no_short, default = "0", help = "number of encoder threads, 0 for one per core")]
+    encode_threads: usize,
+    #[options(no_short, default = "2", help = "number of threads writing documents")]
+    emit_threads: usize,
+    #[options(no_short, default = "64", help = "encoded documents queued before encoders wait")]
+    queue_size: usize,
+    #[options(no_short, help = "key=value front-matter to add to emitted Markdown pages")]
+    front_matter: Vec<String>,
+    #[options(
+        no_short,
+        help = "add parameter, local and code size counts of functions to the index"
+    )]
+    function_stats: bool,
+    #[options(no_short, help = "also write the index split by first letter into index/")]
+    split_index: bool,
+    #[options(no_short, help = "write a Discord embed for every definition into embeds/")]
+    discord_embeds: bool,
+    #[options(no_short, help = "write a hover-card document for every definition into cards/")]
+    cards: bool,
+    #[options(
+        no_short,
+        help = "link template for embeds and cards, {index} and {name} are substituted"
+    )]
+    embed_link: Option<String>,
+    #[options(
+        no_short,
+        help = "try unsupported bundle versions and skip definitions that fail to encode"
+    )]
+    best_effort: bool,
+    #[options(no_short, help = "write a DocC symbol graph for this module name")]
+    symbol_graph: Option<String>,
+    #[options(
+        no_short,
+        help = "only regenerate definitions from this changed source file and patch the existing output"
+    )]
+    changed_source: Vec<PathBuf>,
+}
+
+#[derive(Debug, Options)]
+enum Command {
+    #[options(help = "write the document of a single definition")]
+    Extract(ExtractOpts),
+    #[options(help = "verify that an output directory is up-to-date with a bundle")]
+    Check(CheckOpts),
+    #[options(help = "report which symbols used by a mod changed between two versions")]
+    Advise(AdviseOpts),
+    #[options(help = "verify the signature and the integrity of an output directory")]
+    Verify(VerifyOpts),
+    #[options(help = "render pages from a previously generated output directory")]
+    Render(RenderOpts),
+}
+
+#[derive(Debug, Options)]
+struct ExtractOpts {
+    #[options(free, required, help = "name of the definition to extract")]
+    symbol: String,
     #[options(required, short = "i", help = "redscript bundle file to read")]
     input: PathBuf,
-    #[options(required, short = "o", help = "output directory")]
+    #[options(short = "o", help = "file to write to, defaults to stdout")]
+    output: Option<PathBuf>,
+    #[options(short = "f", default = "json", help = "output format: json, md or reds")]
+    format: Format,
+    #[options(no_short, help = "key=value front-matter to add to Markdown output")]
+    front_matter: Vec<String>,
+}
+
+#[derive(Debug, Options)]
+struct CheckOpts {
+    #[options(required, short = "i", help = "redscript bundle file to check against")]
+    input: PathBuf,
+    #[options(required, short = "o", help = "output directory to check")]
     output: PathBuf,
 }
 
+#[derive(Debug, Options)]
+struct RenderOpts {
+    #[options(free, help = "names of the definitions to render, all of them if none are given")]
+    symbols: Vec<String>,
+    #[options(
+        required,
+        short = "i",
+        help = "output directory of a previous run to read documents from"
+    )]
+    input: PathBuf,
+    #[options(required, short = "o", help = "directory to write pages to")]
+    output: PathBuf,
+    #[options(short = "f", default = "md", help = "page format: md or reds")]
+    format: Format,
+    #[options(no_short, help = "key=value front-matter to add to Markdown pages")]
+    front_matter: Vec<String>,
+}
+
+#[derive(Debug, Options)]
+struct VerifyOpts {
+    #[options(required, short = "o", help = "output directory to verify")]
+    output: PathBuf,
+    #[options(required, no_short, help = "file with the hex-encoded ed25519 public key to trust")]
+    public_key: PathBuf,
+}
+
+#[derive(Debug, Options)]
+struct AdviseOpts {
+    #[options(required, short = "c", help = "changes.json written by a run with --previous")]
+    changes: PathBuf,
+    #[options(short = "s", help = "file listing the symbols used by the mod, one per line")]
+    symbols: Option<PathBuf>,
+    #[options(short = "b", help = "bundle to collect the symbols used by the mod from")]
+    bundle: Option<PathBuf>,
+    #[options(no_short, help = "only collect symbols from code under this source path")]
+    source_prefix: Option<PathBuf>,
+    #[options(short = "o", help = "file to write to, defaults to stdout")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Format {
+    Json,
+    Markdown,
+    Redscript,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "md" => Ok(Format::Markdown),
+            "reds" => Ok(Format::Redscript),
+            other => Err(format!("unknown format {}", other)),
+        }
+    }
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let args: Vec<String> = std::env::args().skip(1).collect();
     let opts = AppOpts::parse_args_default(&args)?;
 
-    let bundle = ScriptBundle::load(&mut BufReader::new(File::open(opts.input)?))?;
-    let pool = Arc::new(&bundle.pool);
-    std::fs::create_dir_all(&opts.output)?;
+    match opts.command {
+        Some(Command::Extract(extract)) => run_extract(extract),
+        Some(Command::Check(check)) => run_check(check),
+        Some(Command::Advise(advise)) => run_advise(advise),
+        Some(Command::Verify(verify)) => run_verify(verify),
+        Some(Command::Render(render)) => run_render(render),
+        None => generate(opts),
+    }
+}
+
+fn generate(opts: AppOpts) -> Result<(), Box<dyn Error>> {
+    let (Some(input), Some(output)) = (opts.input, opts.output) else {
+        return Err("both --input and --output are required".into());
+    };
+    let front_matter = FrontMatter::parse(&opts.front_matter)?;
+    let partial = !opts.changed_source.is_empty();
+    if partial {
+        let unsupported = [
+            ("--split-index", opts.split_index),
+            ("--deny-warnings", opts.deny_warnings),
+            ("--history", opts.history.is_some()),
+            ("--previous", opts.previous.is_some()),
+            ("--newer", opts.newer.is_some()),
+            ("--symbol-graph", opts.symbol_graph.is_some()),
+        ];
+        if let Some((flag, _)) = unsupported.iter().find(|(_, set)| *set) {
+            return Err(format!(
+                "--changed-source can't be combined with {}, regenerate everything instead",
+                flag
+            )
+            .into());
+        }
+    }
 
-    let pool = pool.clone();
+    let bundle = loader::load_bundle(&input, opts.best_effort)?;
+    let pool = &bundle.pool;
+    let native_db = opts.native_db.as_deref().map(NativeDb::load).transpose()?;
+    let analysis = match &opts.analysis_cache {
+        Some(dir) => Analysis::cached(pool, &manifest::hash_file(&input)?, dir)?,
+        None => Analysis::build(pool)?,
+    };
+    let analysis = analysis.with_max_type_depth(opts.max_type_depth);
+    let previous = opts.previous.as_deref().map(Snapshot::load).transpose()?;
+    let newer = opts.newer.as_deref().map(Snapshot::load).transpose()?;
+    let ids = match &opts.id_map {
+        Some(path) => {
+            let mut ids = StableIds::load(path)?;
+            ids.assign(pool)?;
+            ids.save(path)?;
+            Some(ids)
+        }
+        None => None,
+    };
+    // churn of the versions recorded before this one, the current run is recorded at the end
+    let churn = opts
+        .history
+        .as_deref()
+        .map(|path| History::open(path)?.churn())
+        .transpose()?;
+    std::fs::create_dir_all(&output)?;
+    let embeds_dir = output.join("embeds");
+    if opts.discord_embeds {
+        std::fs::create_dir_all(&embeds_dir)?;
+    }
+    let cards_dir = output.join("cards");
+    if opts.cards {
+        std::fs::create_dir_all(&cards_dir)?;
+    }
 
-    pool.clone()
+    let subtree = match &opts.root_class {
+        Some(name) => Some(subtree::collect_subtree(pool, name)?),
+        None => None,
+    };
+    let ignore = opts.ignore_file.as_deref().map(IgnoreList::load).transpose()?;
+    let mut roots: Vec<(PoolIndex<Definition>, &Definition)> = pool
         .roots()
-        .par_bridge()
-        .filter(|(_, def)| {
-            matches!(&def.value, AnyDefinition::Class(_))
-                || matches!(&def.value, AnyDefinition::Function(_))
-                || matches!(&def.value, AnyDefinition::Enum(_))
+        .filter(|(idx, def)| {
+            let verdict = match &ignore {
+                Some(ignore) => pool.names.get(def.name).ok().and_then(|name| ignore.verdict(&name)),
+                None => None,
+            };
+            match verdict {
+                Some(include) => include && is_documented(def),
+                None => is_documented(def) && subtree.as_ref().is_none_or(|set| set.contains(idx)),
+            }
         })
-        .try_for_each(
-            |(idx, def): (PoolIndex<Definition>, &Definition)| -> anyhow::Result<()> {
-                let idx: u32 = idx.into();
-                let path = opts.output.as_path().join(format!("{}.json", idx));
-                let encoded = encode_definition(def, pool.clone())?;
-                std::fs::write(path, serde_json::to_string(&encoded)?)?;
-                Ok(())
-            },
-        )?;
-
-    let index_path = opts.output.as_path().join("index.json");
-    let index = build_index(&pool);
-    std::fs::write(index_path, serde_json::to_string(&index)?)?;
-    Ok(())
-}
+        .collect();
+    if partial {
+        roots = scoped::roots_from_sources(pool, &roots, &opts.changed_source)?;
+        scoped::check_index(&output, pool, &roots)?;
+    }
 
-pub fn encode_definition(definition: &Definition, pool: Arc<&ConstantPool>) -> anyhow::Result<Value> {
-    let result = match &definition.value {
-        AnyDefinition::Type(type_) => match type_ {
-            Type::Prim => json!({"tag": "Type", "kind": "Prim", "name": pool.names.get(definition.name)?.as_ref()}),
-            Type::Class => {
-                let class = find_type(definition.name, &pool).unwrap();
-                let class_idx: u32 = class.into();
-                json!({"tag": "Type", "kind": "Class", "name": pool.names.get(definition.name)?.as_ref(), "index": class_idx })
-            }
-            Type::Ref(inner) => {
-                json!({"tag": "Type", "kind": "Ref", "inner": encode_definition(pool.definition(*inner)?, pool)?})
-            }
-            Type::WeakRef(inner) => {
-                json!({"tag": "Type", "kind": "WeakRef", "inner": encode_definition(pool.definition(*inner)?, pool)?})
+    let mut documents = Documents::new(pool, &analysis)?;
+    documents.enum_order = opts.enum_order;
+    documents.group_enum_aliases = opts.group_enum_aliases;
+    documents.bytecode_dependencies = opts.bytecode_dependencies;
+    documents.native_db = native_db.as_ref();
+    documents.ids = ids.as_ref();
+    documents.newer = newer.as_ref();
+    documents.stability = Stability::new(churn.as_ref());
+    let pipeline = PipelineConfig {
+        encode_threads: opts.encode_threads,
+        emit_threads: opts.emit_threads,
+        queue_size: opts.queue_size,
+    };
+    let recovery = Recovery::new(opts.best_effort);
+    pipeline::run(
+        &roots,
+        pipeline,
+        |&(idx, def): &(PoolIndex<Definition>, &Definition)| -> anyhow::Result<Option<(u32, Value)>> {
+            let encoded = recovery.attempt(idx, documents.encode(idx, def))?;
+            Ok(encoded.map(|encoded| (idx.into(), encoded)))
+        },
+        |item: Option<(u32, Value)>| -> anyhow::Result<()> {
+            let Some((idx, encoded)) = item else {
+                return Ok(());
+            };
+            if opts.discord_embeds {
+                let embed = embed::build_embed(&encoded, idx, opts.embed_link.as_deref());
+                std::fs::write(embeds_dir.join(format!("{}.json", idx)), serde_json::to_string(&embed)?)?;
             }
-            Type::ScriptRef(inner) => {
-                json!({"tag": "Type", "kind": "ScriptRef", "inner": encode_definition(pool.definition(*inner)?, pool)?})
+            if opts.cards {
+                let card = embed::build_card(&encoded, idx, opts.embed_link.as_deref());
+                std::fs::write(cards_dir.join(format!("{}.json", idx)), serde_json::to_string(&card)?)?;
             }
-            Type::Array(inner) => {
-                json!({"tag": "Type", "kind": "Array", "inner": encode_definition(pool.definition(*inner)?, pool)?})
+            let path = output.as_path().join(format!("{}.json", idx));
+            std::fs::write(path, serde_json::to_string(&encoded)?)?;
+            Ok(())
+        },
+    )?;
+
+    roots.retain(|(idx, _)| !recovery.is_skipped(*idx));
+
+    let index_path = output.as_path().join("index.json");
+    let index = build_index(
+        pool,
+        &roots,
+        &analysis.usages,
+        ids.as_ref(),
+        Some(&documents.stability),
+        opts.function_stats,
+    );
+    if partial {
+        let mut touched = scoped::patch_index(&output, &index)?;
+        for (idx, _) in &roots {
+            let file = format!("{}.json", u32::from(*idx));
+            if opts.discord_embeds {
+                touched.push(format!("embeds/{}", file));
             }
-            Type::StaticArray(inner, size) => {
-                json!({"tag": "Type", "kind": "StaticArray", "size": size, "inner": encode_definition(pool.definition(*inner)?, pool)?})
+            if opts.cards {
+                touched.push(format!("cards/{}", file));
             }
-        },
-        AnyDefinition::Class(class) => {
-            let fields: anyhow::Result<Vec<Value>> = class
-                .fields
-                .iter()
-                .map(|f| encode_definition(pool.definition(*f)?, pool.clone()))
-                .collect();
-            let methods: anyhow::Result<Vec<Value>> = class
-                .functions
-                .iter()
-                .map(|f| encode_definition(pool.definition(*f)?, pool.clone()))
-                .collect();
-            json!({
-                "tag": "Class",
-                "name": pool.names.get(definition.name)?.as_ref(),
-                "visibility": format!("{}", class.visibility).to_lowercase(),
-                "bases": collect_bases(class.base, &pool)?,
-                "fields": fields?,
-                "methods": methods?,
-                "isNative": class.flags.is_native(),
-                "isAbstract": class.flags.is_abstract(),
-                "isFinal": class.flags.is_final(),
-                "isStruct": class.flags.is_struct(),
-            })
+            touched.push(file);
         }
-        AnyDefinition::EnumValue(val) => json!({
-            "tag": "EnumValue",
-            "name": pool.names.get(definition.name)?.as_ref(),
-            "value": val,
-        }),
-        AnyDefinition::Enum(enum_) => {
-            let members: anyhow::Result<Vec<Value>> = enum_
-                .members
-                .iter()
-                .map(|m| encode_definition(pool.definition(*m)?, pool.clone()))
-                .collect();
-            json!({
-                "tag": "Enum",
-                "name": pool.names.get(definition.name)?.as_ref(),
-                "members": members?
-            })
+        let mut manifest = Manifest::load(&output)?;
+        manifest.update(&output, &touched)?;
+        manifest.save(&output)?;
+        if let Some(key) = &opts.signing_key {
+            signing::sign_manifest(&output, key)?;
         }
-        AnyDefinition::Function(fun) => {
-            let parameters: anyhow::Result<Vec<Value>> = fun
-                .parameters
-                .iter()
-                .map(|m| encode_definition(pool.definition(*m)?, pool.clone()))
-                .collect();
-            json!({
-                "tag": "Function",
-                "name": pool.names.get(definition.name)?.as_ref(),
-                "parameters": parameters?,
-                "returnType": fun.return_type.map(|idx| encode_definition(pool.definition(idx).unwrap(), pool.clone()).unwrap()),
-                "visibility": format!("{}", fun.visibility).to_lowercase(),
-                "isStatic": fun.flags.is_static(),
-                "isFinal": fun.flags.is_final(),
-                "isExec": fun.flags.is_exec(),
-                "isCallback": fun.flags.is_callback(),
-                "isNative": fun.flags.is_native(),
-                "source": fun.source.as_ref().map(|idx| encode_definition(pool.definition(idx.file).unwrap(), pool.clone()).unwrap())
-            })
+        eprintln!("regenerated {} definitions from the changed sources", roots.len());
+        return Ok(());
+    }
+    std::fs::write(index_path, serde_json::to_string(&index)?)?;
+    if opts.split_index {
+        write_split_index(&output, &index)?;
+    }
+
+    let members_path = output.as_path().join("members.json");
+    let members = members::member_index(pool, &roots)?;
+    std::fs::write(members_path, serde_json::to_string(&members)?)?;
+
+    let hashes_path = output.as_path().join("function-hashes.json");
+    let hashes = hashes::function_hashes(pool)?;
+    std::fs::write(hashes_path, serde_json::to_string(&hashes)?)?;
+
+    let globals_path = output.as_path().join("global-functions.json");
+    let globals = globals::group_globals(pool, &roots)?;
+    std::fs::write(globals_path, serde_json::to_string(&globals)?)?;
+
+    let static_arrays_path = output.as_path().join("static-arrays.json");
+    let static_arrays = stats::static_array_report(pool, &analysis, &recovery)?;
+    std::fs::write(static_arrays_path, serde_json::to_string(&static_arrays)?)?;
+
+    let type_stats_path = output.as_path().join("type-stats.json");
+    let type_stats = stats::type_stats(pool, &analysis, &recovery)?;
+    std::fs::write(type_stats_path, serde_json::to_string(&type_stats)?)?;
+
+    let types_path = output.as_path().join("types.json");
+    let types = build_types(pool, &analysis, &recovery)?;
+    std::fs::write(types_path, serde_json::to_string(&types)?)?;
+
+    if let Some(module) = &opts.symbol_graph {
+        let graph = symbol_graph::symbol_graph(module, pool, &roots, &analysis)?;
+        let graph_path = output.as_path().join(format!("{}.symbols.json", module));
+        std::fs::write(graph_path, serde_json::to_string(&graph)?)?;
+    }
+
+    let map = system_map::system_map(pool, &roots)?;
+    std::fs::write(
+        output.as_path().join("system-map.json"),
+        serde_json::to_string(&map.to_json())?,
+    )?;
+    std::fs::write(
+        output.as_path().join("system-map.md"),
+        front_matter.apply(map.render_markdown()?)?,
+    )?;
+
+    let localization_path = output.as_path().join("localization.json");
+    std::fs::write(localization_path, serde_json::to_string(&analysis.localization)?)?;
+
+    let warnings_path = output.as_path().join("warnings.json");
+    let mut warnings = lint::collect_warnings(pool)?;
+    let skipped = recovery.into_skipped();
+    for (idx, err) in &skipped {
+        warnings.push(lint::warning(
+            "undecodable",
+            format!("{} could not be encoded: {}", pool.def_name(*idx)?, err),
+            *idx,
+            pool,
+        )?);
+    }
+    if !warnings.is_empty() {
+        eprintln!(
+            "found {} suspicious definitions, see {}",
+            warnings.len(),
+            warnings_path.display()
+        );
+    }
+    std::fs::write(warnings_path, serde_json::to_string(&warnings)?)?;
+    if opts.deny_warnings && !warnings.is_empty() {
+        return Err(format!("{} warnings were reported and --deny-warnings is set", warnings.len()).into());
+    }
+
+    let mut changes = None;
+    if previous.is_some() || newer.is_some() || opts.history.is_some() {
+        let current = Snapshot::load(&output)?;
+        if let Some(previous) = &previous {
+            let diff = Diff::compute(previous, &current);
+            std::fs::write(
+                output.join("whats-new.md"),
+                front_matter.apply(diff.render_markdown()?)?,
+            )?;
+            std::fs::write(output.join("changes.json"), serde_json::to_string(&diff.to_json())?)?;
+            changes = Some(diff);
         }
-        AnyDefinition::Parameter(param) => json!({
-            "tag": "Parameter",
-            "name": pool.names.get(definition.name)?.as_ref(),
-            "type": encode_definition(pool.definition(param.type_)?, pool.clone())?,
-            "isOut": param.flags.is_out(),
-            "isOptional": param.flags.is_optional(),
-        }),
-        AnyDefinition::Field(field) => json!({
-            "tag": "Field",
-            "name": pool.names.get(definition.name)?.as_ref(),
-            "type": encode_definition(pool.definition(field.type_)?, pool.clone())?,
-            "isNative": field.flags.is_native(),
-            "isEdit": field.flags.is_editable(),
-            "isInline": field.flags.is_inline(),
-            "isConst": field.flags.is_const(),
-            "isRep": field.flags.is_replicated(),
-            "isPersistent": field.flags.is_persistent(),
-        }),
-        AnyDefinition::SourceFile(f) => Value::String(f.path.display().to_string()),
-        AnyDefinition::Local(_) => panic!(),
-    };
-    Ok(result)
-}
+        if let Some(newer) = &newer {
+            let diff = Diff::compute(&current, newer);
+            std::fs::write(
+                output.join("deprecations.md"),
+                front_matter.apply(diff.render_deprecations()?)?,
+            )?;
+        }
+        if let Some(path) = &opts.history {
+            let label = match &opts.version_label {
+                Some(label) => label.clone(),
+                None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs().to_string(),
+            };
+            let mut history = History::open(path)?;
+            // a filtered output lacks the symbols left out, which the history would take as removed
+            if opts.root_class.is_some() || opts.ignore_file.is_some() {
+                eprintln!(
+                    "not recording {} in the history, --root-class and --ignore-file leave symbols out",
+                    label
+                );
+            } else if !skipped.is_empty() {
+                eprintln!(
+                    "not recording {} in the history, {} definitions could not be encoded",
+                    label,
+                    skipped.len()
+                );
+            } else {
+                history.record(&label, &current)?;
+            }
+            let churn = history.churn()?;
+            std::fs::write(output.join("churn.json"), serde_json::to_string(&churn.to_json())?)?;
+            std::fs::write(output.join("churn.md"), front_matter.apply(churn.render_markdown()?)?)?;
+        }
+    }
+
+    Manifest::build(&input, &output)?.save(&output)?;
+    if let Some(key) = &opts.signing_key {
+        signing::sign_manifest(&output, key)?;
+    }
 
-fn find_type(name: PoolIndex<CName>, pool: &ConstantPool) -> Option<PoolIndex<Class>> {
-    pool.definitions().find_map(|(idx, def)| match &def.value {
-        AnyDefinition::Class(_) if def.name == name => Some(idx.cast()),
-        AnyDefinition::Enum(_) if def.name == name => Some(idx.cast()),
-        _ => None,
-    })
+    // the output is complete at this point, a failed notification shouldn't fail the run
+    if let (Some(url), Some(diff)) = (&opts.webhook, &changes) {
+        if let Err(err) = webhook::notify(url, opts.webhook_format, diff, opts.version_label.as_deref()) {
+            eprintln!("failed to send the webhook notification: {}", err);
+        }
+    }
+    Ok(())
 }
 
-fn build_index(pool: &ConstantPool) -> Vec<Reference> {
-    pool.roots()
-        .filter(|(_, def)| {
-            matches!(&def.value, AnyDefinition::Class(_))
-                || matches!(&def.value, AnyDefinition::Function(_))
-                || matches!(&def.value, AnyDefinition::Enum(_))
-        })
-        .map(|(index, def)| {
-            let name = pool.names.get(def.name).unwrap();
-            let pretty = Arc::from(name.split(';').next().unwrap());
-            let base = def.value.as_class().map(|c| c.base.cast());
-            Reference {
-                name: pretty,
-                index,
-                base,
-            }
-        })
-        .collect()
+fn run_check(opts: CheckOpts) -> Result<(), Box<dyn Error>> {
+    let problems = Manifest::load(&opts.output)?.check(&opts.input, &opts.output)?;
+    if problems.is_empty() {
+        return Ok(());
+    }
+    for problem in &problems {
+        eprintln!("{}", problem);
+    }
+    Err(format!("{} is out of date ({} problems)", opts.output.display(), problems.len()).into())
 }
 
-fn collect_bases(idx: PoolIndex<Class>, pool: &ConstantPool) -> anyhow::Result<Vec<Reference>> {
-    let mut bases = vec![];
-    if idx != PoolIndex::UNDEFINED {
-        let reference = Reference {
-            name: pool.def_name(idx)?,
-            index: idx.cast(),
-            base: None,
+fn run_render(opts: RenderOpts) -> Result<(), Box<dyn Error>> {
+    let front_matter = FrontMatter::parse(&opts.front_matter)?;
+    let index = diff::read_json(&opts.input.join("index.json"))?;
+    std::fs::create_dir_all(&opts.output)?;
+    for entry in index.as_array().into_iter().flatten() {
+        let (Some(idx), Some(name)) = (entry["index"].as_u64(), entry["name"].as_str()) else {
+            continue;
+        };
+        if !opts.symbols.is_empty() && !opts.symbols.iter().any(|symbol| symbol == name) {
+            continue;
+        }
+        let doc = diff::read_json(&opts.input.join(format!("{}.json", idx)))?;
+        let (contents, extension) = match opts.format {
+            Format::Markdown => (front_matter.apply(render::render_markdown(&doc)?)?, "md"),
+            Format::Redscript => (render::render_redscript(&doc)?, "reds"),
+            Format::Json => return Err("documents are already JSON, pick md or reds".into()),
         };
-        let class = pool.class(idx)?;
-        bases.push(reference);
-        bases.append(&mut collect_bases(class.base, pool)?);
+        std::fs::write(opts.output.join(format!("{}.{}", idx, extension)), contents)?;
     }
-    Ok(bases)
+    Ok(())
 }
 
-pub struct Reference {
-    name: Arc<str>,
-    index: PoolIndex<Definition>,
-    base: Option<PoolIndex<Definition>>,
+fn run_verify(opts: VerifyOpts) -> Result<(), Box<dyn Error>> {
+    signing::verify_manifest(&opts.output, &opts.public_key)?;
+    let problems = Manifest::load(&opts.output)?.check_files(&opts.output)?;
+    if problems.is_empty() {
+        return Ok(());
+    }
+    for problem in &problems {
+        eprintln!("{}", problem);
+    }
+    Err(format!(
+        "{} does not match its manifest ({} problems)",
+        opts.output.display(),
+        problems.len()
+    )
+    .into())
 }
 
-impl Serialize for Reference {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let mut state = serializer.serialize_struct("Reference", 3)?;
-        state.serialize_field("name", self.name.as_ref())?;
-        state.serialize_field("index", &u32::from(self.index))?;
-        state.serialize_field("base", &self.base.map(u32::from))?;
-        state.end()
+fn run_advise(opts: AdviseOpts) -> Result<(), Box<dyn Error>> {
+    let mut symbols = match &opts.symbols {
+        Some(path) => advisor::read_symbols(path)?,
+        None => Default::default(),
+    };
+    if let Some(path) = &opts.bundle {
+        let bundle = loader::load_bundle(path, false)?;
+        symbols.extend(advisor::referenced_symbols(
+            &bundle.pool,
+            opts.source_prefix.as_deref(),
+        )?);
+    }
+    if opts.symbols.is_none() && opts.bundle.is_none() {
+        return Err("either --symbols or --bundle is required".into());
+    }
+
+    let changes = diff::read_json(&opts.changes)?;
+    let advice = advisor::advise(&changes, &symbols);
+    let report = advisor::render_markdown(&advice, symbols.len())?;
+    match &opts.output {
+        Some(path) => std::fs::write(path, report)?,
+        None => std::io::stdout().write_all(report.as_bytes())?,
     }
+    Ok(())
+}
+
+fn run_extract(opts: ExtractOpts) -> Result<(), Box<dyn Error>> {
+    let bundle = loader::load_bundle(&opts.input, false)?;
+    let pool = &bundle.pool;
+    let (idx, def) = pool
+        .roots()
+        .filter(|(_, def)| is_documented(def))
+        .find(|(_, def)| {
+            pool.names
+                .get(def.name)
+                .is_ok_and(|name| *name == *opts.symbol || render::unmangled(&name) == opts.symbol)
+        })
+        .ok_or_else(|| format!("definition {} not found in the bundle", opts.symbol))?;
+
+    let analysis = Analysis::build(pool)?;
+    let encoded = Documents::new(pool, &analysis)?.encode(idx, def)?;
+    let contents = match opts.format {
+        Format::Json => serde_json::to_string_pretty(&encoded)?,
+        Format::Markdown => FrontMatter::parse(&opts.front_matter)?.apply(render::render_markdown(&encoded)?)?,
+        Format::Redscript => render::render_redscript(&encoded)?,
+    };
+    match &opts.output {
+        Some(path) => std::fs::write(path, contents)?,
+        None => std::io::stdout().write_all(contents.as_bytes())?,
+    }
+    Ok(())
 }
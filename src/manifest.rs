@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::diff::read_json;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+pub const SIGNATURE_FILE: &str = "manifest.json.sig";
+
+/// Records the bundle an output directory was generated from along with hashes of every emitted file.
+pub struct Manifest {
+    generator_version: String,
+    bundle_hash: String,
+    files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    pub fn build(bundle: &Path, output: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            generator_version: env!("CARGO_PKG_VERSION").to_owned(),
+            bundle_hash: hash_file(bundle)?,
+            files: hash_files(output)?,
+        })
+    }
+
+    pub fn load(output: &Path) -> anyhow::Result<Self> {
+        let manifest = read_json(&output.join(MANIFEST_FILE))?;
+        let files = manifest["files"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, hash)| Some((name.clone(), hash.as_str()?.to_owned())))
+            .collect();
+        Ok(Self {
+            generator_version: manifest["generatorVersion"].as_str().unwrap_or_default().to_owned(),
+            bundle_hash: manifest["bundleHash"].as_str().unwrap_or_default().to_owned(),
+            files,
+        })
+    }
+
+    pub fn save(&self, output: &Path) -> anyhow::Result<()> {
+        std::fs::write(
+            output.join(MANIFEST_FILE),
+            serde_json::to_string_pretty(&self.to_json())?,
+        )?;
+        Ok(())
+    }
+
+    /// Refreshes the hashes of the given files after a partial regeneration, dropping the ones
+    /// that no longer exist. The bundle hash is kept, since the pool-wide files weren't
+    /// regenerated, so `check` keeps reporting the output as stale until a full run.
+    pub fn update(&mut self, output: &Path, files: &[String]) -> anyhow::Result<()> {
+        for name in files {
+            let path = output.join(name);
+            if path.exists() {
+                self.files.insert(name.clone(), hash_file(&path)?);
+            } else {
+                self.files.remove(name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Compares the manifest against a bundle and the current contents of an output directory,
+    /// returning a description of every inconsistency found.
+    pub fn check(&self, bundle: &Path, output: &Path) -> anyhow::Result<Vec<String>> {
+        let mut problems = vec![];
+        if self.generator_version != env!("CARGO_PKG_VERSION") {
+            problems.push(format!(
+                "output was generated by version {} of the generator, this is {}",
+                self.generator_version,
+                env!("CARGO_PKG_VERSION")
+            ));
+        }
+        if self.bundle_hash != hash_file(bundle)? {
+            problems.push("output was generated from a different bundle".to_owned());
+        }
+        problems.extend(self.check_files(output)?);
+        Ok(problems)
+    }
+
+    /// Compares the file hashes of the manifest against the current contents of an output directory.
+    pub fn check_files(&self, output: &Path) -> anyhow::Result<Vec<String>> {
+        let mut problems = vec![];
+        let actual = hash_files(output)?;
+        for (name, hash) in &self.files {
+            match actual.get(name) {
+                None => problems.push(format!("{} is missing", name)),
+                Some(current) if current != hash => problems.push(format!("{} was modified", name)),
+                Some(_) => {}
+            }
+        }
+        for name in actual.keys() {
+            if !self.files.contains_key(name) {
+                problems.push(format!("{} is not listed in the manifest", name));
+            }
+        }
+        Ok(problems)
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "generatorVersion": self.generator_version,
+            "bundleHash": self.bundle_hash,
+            "files": self.files,
+        })
+    }
+}
+
+pub fn hash_file(path: &Path) -> anyhow::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn hash_files(output: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    for path in collect_files(output)? {
+        let name = relative_name(output, &path)?;
+        if name != MANIFEST_FILE && name != SIGNATURE_FILE {
+            files.insert(name, hash_file(&path)?);
+        }
+    }
+    Ok(files)
+}
+
+fn collect_files(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(collect_files(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+fn relative_name(root: &Path, path: &Path) -> anyhow::Result<String> {
+    let components: Vec<String> = path
+        .strip_prefix(root)?
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Ok(components.join("/"))
+}
@@ -0,0 +1,33 @@
+use std::collections::BTreeMap;
+
+use redscript::bundle::{ConstantPool, PoolIndex};
+use redscript::definition::{AnyDefinition, Definition};
+use serde_json::{json, Value};
+
+use crate::render::unmangled;
+
+/// Maps the names of methods and fields to the classes declaring a member with that name.
+///
+/// Method names are keyed without their signature suffix, so overloads share an entry.
+pub fn member_index(pool: &ConstantPool, roots: &[(PoolIndex<Definition>, &Definition)]) -> anyhow::Result<Value> {
+    let mut methods: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    let mut fields: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    for &(idx, def) in roots {
+        let AnyDefinition::Class(class) = &def.value else {
+            continue;
+        };
+        let owner = json!({"name": pool.names.get(def.name)?.as_ref(), "index": u32::from(idx)});
+        for method in &class.functions {
+            let name = pool.def_name(*method)?;
+            let owners = methods.entry(unmangled(&name).to_owned()).or_default();
+            if !owners.contains(&owner) {
+                owners.push(owner.clone());
+            }
+        }
+        for field in &class.fields {
+            let name = pool.def_name(*field)?;
+            fields.entry(name.to_string()).or_default().push(owner.clone());
+        }
+    }
+    Ok(json!({"methods": methods, "fields": fields}))
+}
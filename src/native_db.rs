@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use anyhow::bail;
+use serde_json::{json, Value};
+
+use crate::render::unmangled;
+
+/// Native type information imported from an RTTI/NativeDB dump.
+///
+/// The dump is expected to be a JSON array of classes (or an object with a `classes` array),
+/// where each class has a `name` and optional `props` (`name`, `type`, `offset`) and
+/// `funcs` (`name`, `returnType`, `params`) arrays.
+pub struct NativeDb {
+    classes: HashMap<String, Value>,
+}
+
+impl NativeDb {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let dump: Value = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        let entries = match dump {
+            Value::Array(entries) => entries,
+            Value::Object(mut obj) => match obj.remove("classes") {
+                Some(Value::Array(entries)) => entries,
+                _ => bail!("native dump has no classes array"),
+            },
+            _ => bail!("native dump must be an array or an object"),
+        };
+        let classes = entries
+            .into_iter()
+            .filter_map(|entry| Some((entry["name"].as_str()?.to_owned(), entry)))
+            .collect();
+        Ok(Self { classes })
+    }
+
+    /// Fills in members and details that the bundle lacks into an encoded class document.
+    pub fn merge_class(&self, doc: &mut Value) {
+        if doc["tag"] != "Class" {
+            return;
+        }
+        let Some(native) = doc["name"].as_str().and_then(|name| self.classes.get(name)) else {
+            return;
+        };
+        if let (Some(fields), Some(props)) = (doc["fields"].as_array_mut(), native["props"].as_array()) {
+            merge_fields(fields, props);
+        }
+        if let (Some(methods), Some(funcs)) = (doc["methods"].as_array_mut(), native["funcs"].as_array()) {
+            merge_methods(methods, funcs);
+        }
+    }
+}
+
+fn merge_fields(fields: &mut Vec<Value>, props: &[Value]) {
+    for prop in props {
+        let Some(name) = prop["name"].as_str() else {
+            continue;
+        };
+        match fields.iter_mut().find(|field| field["name"] == name) {
+            Some(field) => {
+                if let Some(offset) = prop.get("offset") {
+                    field["offset"] = offset.clone();
+                }
+            }
+            None => fields.push(json!({
+                "tag": "Field",
+                "name": name,
+                "type": native_type(&prop["type"]),
+                "offset": prop.get("offset"),
+                "isNative": true,
+                "isImported": true,
+            })),
+        }
+    }
+}
+
+fn merge_methods(methods: &mut Vec<Value>, funcs: &[Value]) {
+    for func in funcs {
+        let Some(name) = func["name"].as_str() else {
+            continue;
+        };
+        let existing = methods
+            .iter_mut()
+            .find(|method| method["name"].as_str().map(unmangled) == Some(name));
+        match existing {
+            Some(method) => {
+                if method["returnType"].is_null() && has_return_type(func) {
+                    method["returnType"] = native_type(&func["returnType"]);
+                }
+            }
+            None => {
+                let parameters: Vec<Value> = func["params"]
+                    .as_array()
+                    .map(Vec::as_slice)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|param| {
+                        json!({
+                            "tag": "Parameter",
+                            "name": param["name"],
+                            "type": native_type(&param["type"]),
+                            "isOut": param["isOut"].as_bool().unwrap_or(false),
+                            "isOptional": param["isOptional"].as_bool().unwrap_or(false),
+                        })
+                    })
+                    .collect();
+                methods.push(json!({
+                    "tag": "Function",
+                    "name": name,
+                    "parameters": parameters,
+                    "returnType": has_return_type(func).then(|| native_type(&func["returnType"])),
+                    "isStatic": func["isStatic"].as_bool().unwrap_or(false),
+                    "isNative": true,
+                    "isImported": true,
+                }));
+            }
+        }
+    }
+}
+
+fn has_return_type(func: &Value) -> bool {
+    func["returnType"].as_str().is_some_and(|ret| !ret.is_empty())
+}
+
+fn native_type(name: &Value) -> Value {
+    json!({"tag": "Type", "kind": "Native", "name": name})
+}
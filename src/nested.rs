@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+use redscript::bundle::{ConstantPool, PoolIndex};
+use redscript::definition::{AnyDefinition, Definition};
+use serde_json::Value;
+
+use crate::analysis::Analysis;
+use crate::encode_definition;
+
+/// Definitions scoped to a class that aren't among its fields and methods, like inner enums or
+/// constants, which would otherwise be skipped since they aren't roots.
+pub struct InnerDefinitions {
+    children: HashMap<PoolIndex<Definition>, Vec<PoolIndex<Definition>>>,
+}
+
+impl InnerDefinitions {
+    pub fn build(pool: &ConstantPool) -> anyhow::Result<Self> {
+        let mut children: HashMap<PoolIndex<Definition>, Vec<PoolIndex<Definition>>> = HashMap::new();
+        for (idx, def) in pool.definitions() {
+            if def.parent == PoolIndex::UNDEFINED
+                || matches!(
+                    &def.value,
+                    AnyDefinition::Parameter(_)
+                        | AnyDefinition::Local(_)
+                        | AnyDefinition::Type(_)
+                        | AnyDefinition::SourceFile(_)
+                        | AnyDefinition::EnumValue(_)
+                )
+            {
+                continue;
+            }
+            let AnyDefinition::Class(owner) = &pool.definition(def.parent)?.value else {
+                continue;
+            };
+            let is_member = owner.fields.iter().any(|field| field.cast::<Definition>() == idx)
+                || owner.functions.iter().any(|fun| fun.cast::<Definition>() == idx);
+            if !is_member {
+                children.entry(def.parent).or_default().push(idx);
+            }
+        }
+        Ok(Self { children })
+    }
+
+    /// Encodes the inner definitions of a class with names qualified by their owner, recursing
+    /// into inner classes. Returns `None` when the class has none.
+    pub fn encode(
+        &self,
+        owner: PoolIndex<Definition>,
+        pool: &ConstantPool,
+        analysis: &Analysis,
+    ) -> anyhow::Result<Option<Value>> {
+        self.encode_nested(owner, pool, analysis, &mut vec![])
+    }
+
+    /// Fails if an owner is nested in itself or deeper than the analysis allows.
+    fn encode_nested(
+        &self,
+        owner: PoolIndex<Definition>,
+        pool: &ConstantPool,
+        analysis: &Analysis,
+        path: &mut Vec<PoolIndex<Definition>>,
+    ) -> anyhow::Result<Option<Value>> {
+        let Some(children) = self.children.get(&owner) else {
+            return Ok(None);
+        };
+        if path.contains(&owner) {
+            bail!("definition {} is nested in itself", u32::from(owner));
+        }
+        if path.len() >= analysis.max_type_depth() {
+            bail!(
+                "definition {} is nested deeper than {} levels",
+                u32::from(owner),
+                analysis.max_type_depth()
+            );
+        }
+        path.push(owner);
+        let qualifier = pool.def_name(owner)?;
+        let mut inner = vec![];
+        for &idx in children {
+            let def = pool.definition(idx)?;
+            let mut encoded = encode_definition(def, pool, analysis)?;
+            let name = pool.names.get(def.name)?;
+            encoded["qualifiedName"] = format!("{}.{}", qualifier, name).into();
+            if let Some(nested) = self.encode_nested(idx, pool, analysis, path)? {
+                encoded["inner"] = nested;
+            }
+            inner.push(encoded);
+        }
+        path.pop();
+        Ok(Some(Value::Array(inner)))
+    }
+}
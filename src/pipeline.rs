@@ -0,0 +1,76 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::sync_channel;
+use std::sync::Mutex;
+
+use anyhow::anyhow;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+/// Parallelism of the encode and emit stages of generation.
+#[derive(Debug, Clone, Copy)]
+pub struct PipelineConfig {
+    /// Number of encoder threads, 0 picks one per core.
+    pub encode_threads: usize,
+    pub emit_threads: usize,
+    /// Number of encoded items that may wait for an emitter before encoders block.
+    pub queue_size: usize,
+}
+
+/// Runs `encode` over the items on a pool of encoder threads and hands the results to `emit` on
+/// separate emitter threads through a bounded channel, so that writing files overlaps with encoding
+/// without buffering every document in memory.
+///
+/// The first error stops the encoders, and the emitters skip whatever is still queued.
+pub fn run<T, O, E, W>(items: &[T], config: PipelineConfig, encode: E, emit: W) -> anyhow::Result<()>
+where
+    T: Sync,
+    O: Send,
+    E: Fn(&T) -> anyhow::Result<O> + Sync,
+    W: Fn(O) -> anyhow::Result<()> + Sync,
+{
+    let encoders = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.encode_threads)
+        .build()?;
+    let (sender, receiver) = sync_channel::<O>(config.queue_size);
+    let receiver = Mutex::new(receiver);
+    let failed = AtomicBool::new(false);
+    let error: Mutex<Option<anyhow::Error>> = Mutex::new(None);
+    let fail = |err: anyhow::Error| {
+        failed.store(true, Ordering::Relaxed);
+        error.lock().unwrap().get_or_insert(err);
+    };
+
+    std::thread::scope(|scope| {
+        for _ in 0..config.emit_threads.max(1) {
+            scope.spawn(|| loop {
+                let Ok(item) = receiver.lock().unwrap().recv() else {
+                    break;
+                };
+                if failed.load(Ordering::Relaxed) {
+                    continue;
+                }
+                if let Err(err) = emit(item) {
+                    fail(err);
+                }
+            });
+        }
+
+        let encoded = encoders.install(|| {
+            items.par_iter().try_for_each_with(sender, |sender, item| {
+                if failed.load(Ordering::Relaxed) {
+                    return Err(anyhow!("generation stopped after an error"));
+                }
+                sender
+                    .send(encode(item)?)
+                    .map_err(|_| anyhow!("emitters stopped before encoding finished"))
+            })
+        });
+        if let Err(err) = encoded {
+            fail(err);
+        }
+    });
+
+    match error.into_inner().unwrap() {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,50 @@
+use std::sync::Mutex;
+
+use redscript::bundle::PoolIndex;
+use redscript::definition::Definition;
+
+/// Decides what happens when a definition fails to encode: normally the error ends the run, in
+/// best-effort mode the definition is skipped and the error is kept to be reported as a warning.
+pub struct Recovery {
+    best_effort: bool,
+    skipped: Mutex<Vec<(PoolIndex<Definition>, anyhow::Error)>>,
+}
+
+impl Recovery {
+    pub fn new(best_effort: bool) -> Self {
+        Self {
+            best_effort,
+            skipped: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn strict() -> Self {
+        Self::new(false)
+    }
+
+    /// Passes the result through, or records the error of a definition and yields `None` in
+    /// best-effort mode.
+    pub fn attempt<A, T>(&self, idx: PoolIndex<A>, result: anyhow::Result<T>) -> anyhow::Result<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if self.best_effort => {
+                let idx: PoolIndex<Definition> = idx.cast();
+                let mut skipped = self.skipped.lock().unwrap();
+                if skipped.iter().all(|(skipped, _)| *skipped != idx) {
+                    skipped.push((idx, err));
+                }
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn is_skipped<A>(&self, idx: PoolIndex<A>) -> bool {
+        let idx: PoolIndex<Definition> = idx.cast();
+        self.skipped.lock().unwrap().iter().any(|(skipped, _)| *skipped == idx)
+    }
+
+    pub fn into_skipped(self) -> Vec<(PoolIndex<Definition>, anyhow::Error)> {
+        self.skipped.into_inner().unwrap()
+    }
+}
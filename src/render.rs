@@ -0,0 +1,306 @@
+use std::fmt::Write;
+
+use anyhow::anyhow;
+use serde_json::Value;
+
+/// Key/value pairs prepended to every emitted Markdown page as YAML front-matter.
+#[derive(Debug, Default)]
+pub struct FrontMatter(Vec<(String, String)>);
+
+impl FrontMatter {
+    /// Parses `key=value` entries.
+    pub fn parse(entries: &[String]) -> anyhow::Result<Self> {
+        let pairs: anyhow::Result<Vec<(String, String)>> = entries
+            .iter()
+            .map(|entry| {
+                let (key, value) = entry
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("front-matter entry {} is not in key=value form", entry))?;
+                Ok((key.trim().to_owned(), value.trim().to_owned()))
+            })
+            .collect();
+        Ok(Self(pairs?))
+    }
+
+    pub fn apply(&self, page: String) -> anyhow::Result<String> {
+        if self.0.is_empty() {
+            return Ok(page);
+        }
+        let mut out = String::from("---\n");
+        for (key, value) in &self.0 {
+            // JSON strings are valid YAML scalars, which takes care of quoting
+            writeln!(out, "{}: {}", key, serde_json::to_string(value)?)?;
+        }
+        writeln!(out, "---\n")?;
+        out.push_str(&page);
+        Ok(out)
+    }
+}
+
+/// Renders an encoded document as a Markdown page.
+pub fn render_markdown(doc: &Value) -> anyhow::Result<String> {
+    let mut out = String::new();
+    writeln!(out, "# {}\n", pretty_name(&doc["name"]))?;
+    match doc["tag"].as_str() {
+        Some("Class") => {
+            writeln!(out, "```\n{}\n```", class_header(doc))?;
+            let fields = members(doc, "fields");
+            if !fields.is_empty() {
+                writeln!(out, "\n## Fields\n\n| Name | Type | Flags |\n| --- | --- | --- |")?;
+                for field in fields {
+                    writeln!(
+                        out,
+                        "| `{}` | `{}` | {} |",
+                        pretty_name(&field["name"]),
+                        render_type(&field["type"]),
+                        field_flags(field).join(", ")
+                    )?;
+                }
+            }
+            let methods = members(doc, "methods");
+            if !methods.is_empty() {
+                writeln!(out, "\n## Methods\n")?;
+                for method in methods {
+                    writeln!(out, "- `{}`", function_signature(method))?;
+                }
+            }
+            let inner = members(doc, "inner");
+            if !inner.is_empty() {
+                writeln!(out, "\n## Inner definitions\n")?;
+                for def in inner {
+                    writeln!(
+                        out,
+                        "- `{}` ({})",
+                        def["qualifiedName"].as_str().unwrap_or_default(),
+                        def["tag"].as_str().unwrap_or_default().to_lowercase()
+                    )?;
+                }
+            }
+        }
+        Some("Enum") => {
+            writeln!(out, "| Name | Value |\n| --- | --- |")?;
+            for member in members(doc, "members") {
+                writeln!(out, "| `{}` | {} |", pretty_name(&member["name"]), member["value"])?;
+            }
+        }
+        Some("Function") => {
+            writeln!(out, "```\n{}\n```", function_signature(doc))?;
+            if let Some(source) = doc["source"].as_str() {
+                writeln!(out, "\nDefined in `{}`.", source)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(out)
+}
+
+/// Renders an encoded document as a redscript declaration without bodies.
+pub fn render_redscript(doc: &Value) -> anyhow::Result<String> {
+    let mut out = String::new();
+    match doc["tag"].as_str() {
+        Some("Class") => {
+            writeln!(out, "{} {{", class_header(doc))?;
+            for field in members(doc, "fields") {
+                let mut modifiers = String::new();
+                if field["isNative"] == true {
+                    modifiers.push_str("native ");
+                }
+                if field["isConst"] == true {
+                    modifiers.push_str("const ");
+                }
+                writeln!(
+                    out,
+                    "  {}let {}: {};",
+                    modifiers,
+                    pretty_name(&field["name"]),
+                    render_type(&field["type"])
+                )?;
+            }
+            for method in members(doc, "methods") {
+                writeln!(out, "  {};", function_signature(method))?;
+            }
+            writeln!(out, "}}")?;
+        }
+        Some("Enum") => {
+            writeln!(out, "enum {} {{", pretty_name(&doc["name"]))?;
+            for member in members(doc, "members") {
+                writeln!(out, "  {} = {},", pretty_name(&member["name"]), member["value"])?;
+            }
+            writeln!(out, "}}")?;
+        }
+        Some("Function") => writeln!(out, "{};", function_signature(doc))?,
+        _ => {}
+    }
+    Ok(out)
+}
+
+/// Renders an encoded type the way it would be written in redscript.
+pub fn render_type(ty: &Value) -> String {
+    let inner = || render_type(&ty["inner"]);
+    match ty["kind"].as_str() {
+        Some("Ref") => format!("ref<{}>", inner()),
+        Some("WeakRef") => format!("wref<{}>", inner()),
+        Some("ScriptRef") => format!("script_ref<{}>", inner()),
+        Some("Array") => format!("array<{}>", inner()),
+        Some("StaticArray") => format!("[{}; {}]", inner(), ty["size"]),
+        _ => ty["name"].as_str().unwrap_or("?").to_owned(),
+    }
+}
+
+/// Renders a function declaration, e.g. `public static func Foo(a: Int32) -> Bool`.
+pub fn function_signature(fun: &Value) -> String {
+    let mut out = String::new();
+    if let Some(visibility) = fun["visibility"].as_str() {
+        out.push_str(visibility);
+        out.push(' ');
+    }
+    for (flag, modifier) in [
+        ("isStatic", "static"),
+        ("isFinal", "final"),
+        ("isNative", "native"),
+        ("isExec", "exec"),
+        ("isCallback", "cb"),
+    ] {
+        if fun[flag] == true {
+            out.push_str(modifier);
+            out.push(' ');
+        }
+    }
+    let params: Vec<String> = members(fun, "parameters")
+        .iter()
+        .map(|param| {
+            let mut rendered = String::new();
+            if param["isOut"] == true {
+                rendered.push_str("out ");
+            }
+            if param["isOptional"] == true {
+                rendered.push_str("opt ");
+            }
+            rendered.push_str(&format!(
+                "{}: {}",
+                pretty_name(&param["name"]),
+                render_type(&param["type"])
+            ));
+            rendered
+        })
+        .collect();
+    out.push_str(&format!("func {}({})", pretty_name(&fun["name"]), params.join(", ")));
+    if !fun["returnType"].is_null() {
+        out.push_str(&format!(" -> {}", render_type(&fun["returnType"])));
+    }
+    out
+}
+
+/// Synthesizes a one-line description of a document from its structure, e.g.
+/// `class with 42 methods extending VehicleObject; referenced 310 times`.
+pub fn summary_hint(doc: &Value) -> String {
+    let mut parts = vec![];
+    match doc["tag"].as_str() {
+        Some("Class") => {
+            let kind = if doc["isStruct"] == true { "struct" } else { "class" };
+            let mut summary = if doc["isAbstract"] == true {
+                format!("abstract {}", kind)
+            } else {
+                kind.to_owned()
+            };
+            let fields = members(doc, "fields").len();
+            let methods = members(doc, "methods").len();
+            match (fields, methods) {
+                (0, 0) => {}
+                (0, methods) => summary.push_str(&format!(" with {}", plural(methods, "method"))),
+                (fields, 0) => summary.push_str(&format!(" with {}", plural(fields, "field"))),
+                (fields, methods) => summary.push_str(&format!(
+                    " with {} and {}",
+                    plural(fields, "field"),
+                    plural(methods, "method")
+                )),
+            }
+            if let Some(base) = doc["bases"].get(0).and_then(|base| base["name"].as_str()) {
+                summary.push_str(&format!(" extending {}", base));
+            }
+            parts.push(summary);
+        }
+        Some("Enum") => parts.push(format!("enum with {}", plural(members(doc, "members").len(), "member"))),
+        Some("Function") => {
+            let kind = if doc["isNative"] == true {
+                "native function"
+            } else {
+                "function"
+            };
+            let mut summary = format!(
+                "{} taking {}",
+                kind,
+                plural(members(doc, "parameters").len(), "parameter")
+            );
+            if !doc["returnType"].is_null() {
+                summary.push_str(&format!(" returning {}", render_type(&doc["returnType"])));
+            }
+            if doc["isStub"] == true {
+                summary.push_str(" (stub)");
+            }
+            parts.push(summary);
+        }
+        _ => {}
+    }
+    if let Some(count) = doc["usageCount"].as_u64().filter(|count| *count > 0) {
+        parts.push(format!("referenced {}", plural(count as usize, "time")));
+    }
+    parts.join("; ")
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// Renders a class declaration line, e.g. `public abstract class Foo extends Bar`.
+pub fn class_header(class: &Value) -> String {
+    let mut out = String::new();
+    if let Some(visibility) = class["visibility"].as_str() {
+        out.push_str(visibility);
+        out.push(' ');
+    }
+    for (flag, modifier) in [("isAbstract", "abstract"), ("isFinal", "final"), ("isNative", "native")] {
+        if class[flag] == true {
+            out.push_str(modifier);
+            out.push(' ');
+        }
+    }
+    let kind = if class["isStruct"] == true { "struct" } else { "class" };
+    out.push_str(&format!("{} {}", kind, pretty_name(&class["name"])));
+    if let Some(base) = class["bases"].get(0).and_then(|base| base["name"].as_str()) {
+        out.push_str(&format!(" extends {}", base));
+    }
+    out
+}
+
+fn field_flags(field: &Value) -> Vec<&'static str> {
+    [
+        ("isNative", "native"),
+        ("isEdit", "edit"),
+        ("isInline", "inline"),
+        ("isConst", "const"),
+        ("isRep", "replicated"),
+        ("isPersistent", "persistent"),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| field[*flag] == true)
+    .map(|(_, name)| name)
+    .collect()
+}
+
+fn members<'a>(doc: &'a Value, key: &str) -> &'a [Value] {
+    doc[key].as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+pub fn pretty_name(name: &Value) -> &str {
+    unmangled(name.as_str().unwrap_or_default())
+}
+
+/// Strips the parameter types off a mangled function name, other names are left as they are.
+pub fn unmangled(name: &str) -> &str {
+    name.split(';').next().unwrap_or(name)
+}
@@ -0,0 +1,161 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail};
+use redscript::bundle::{ConstantPool, PoolIndex};
+use redscript::definition::{AnyDefinition, Definition, Function};
+use serde_json::Value;
+
+use crate::diff::read_json;
+use crate::render::unmangled;
+use crate::Reference;
+
+/// Directories holding per-definition files next to the documents themselves.
+const PER_DEFINITION_DIRS: &[&str] = &["embeds", "cards"];
+
+/// Narrows the roots down to the definitions originating from any of the changed source files:
+/// functions defined in them and classes with a method defined in them. Paths match when one
+/// ends with the other, so that both absolute paths and paths relative to the scripts
+/// directory can be given.
+pub fn roots_from_sources<'a>(
+    pool: &ConstantPool,
+    roots: &[(PoolIndex<Definition>, &'a Definition)],
+    changed: &[PathBuf],
+) -> anyhow::Result<Vec<(PoolIndex<Definition>, &'a Definition)>> {
+    let is_changed = |fun: &Function| -> anyhow::Result<bool> {
+        let Some(source) = &fun.source else {
+            return Ok(false);
+        };
+        let AnyDefinition::SourceFile(file) = &pool.definition(source.file)?.value else {
+            return Ok(false);
+        };
+        Ok(changed
+            .iter()
+            .any(|path| path.ends_with(&file.path) || file.path.ends_with(path)))
+    };
+
+    let mut scoped = vec![];
+    for &(idx, def) in roots {
+        let include = match &def.value {
+            AnyDefinition::Function(fun) => is_changed(fun)?,
+            AnyDefinition::Class(class) => {
+                let mut include = false;
+                for fun in &class.functions {
+                    include |= is_changed(pool.function(*fun)?)?;
+                }
+                include
+            }
+            _ => false,
+        };
+        if include {
+            scoped.push((idx, def));
+        }
+    }
+    Ok(scoped)
+}
+
+/// Fails when a definition that isn't regenerated no longer has the pool index its entry in the
+/// `index.json` of an output directory records, since its document would keep stale indices.
+pub fn check_index(
+    output: &Path,
+    pool: &ConstantPool,
+    regenerated: &[(PoolIndex<Definition>, &Definition)],
+) -> anyhow::Result<()> {
+    let path = output.join("index.json");
+    let Value::Array(index) = read_json(&path)? else {
+        return Err(anyhow!("{} is not a valid index", path.display()));
+    };
+    // the index lists definitions by their unmangled names
+    let mut names = HashSet::new();
+    for (_, def) in regenerated {
+        names.insert(unmangled(&pool.names.get(def.name)?).to_owned());
+    }
+    for entry in &index {
+        let (Some(idx), Some(name)) = (entry["index"].as_u64(), entry["name"].as_str()) else {
+            continue;
+        };
+        if names.contains(name) {
+            continue;
+        }
+        let current = u32::try_from(idx)
+            .ok()
+            .and_then(|idx| pool.def_name(PoolIndex::<Definition>::new(idx)).ok());
+        if current.as_deref().map(unmangled) != Some(name) {
+            bail!(
+                "{} is no longer at index {} of the bundle, full regeneration required",
+                name,
+                idx
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the entries of the regenerated definitions in the `index.json` of an output
+/// directory, matching them by pool index when the name there agrees, or by name when the index
+/// changed and the name is unambiguous, and appending new ones. Files of definitions that moved
+/// to another pool index are deleted, unless a regenerated definition took that index over.
+///
+/// Returns the names of the files written or deleted, relative to the output directory.
+pub fn patch_index(output: &Path, regenerated: &[Reference]) -> anyhow::Result<Vec<String>> {
+    let path = output.join("index.json");
+    let Value::Array(mut index) = read_json(&path)? else {
+        return Err(anyhow!("{} is not a valid index", path.display()));
+    };
+    let by_index: HashMap<u64, usize> = index
+        .iter()
+        .enumerate()
+        .filter_map(|(i, entry)| Some((entry["index"].as_u64()?, i)))
+        .collect();
+    let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, entry) in index.iter().enumerate() {
+        if let Some(name) = entry["name"].as_str() {
+            by_name.entry(name.to_owned()).or_default().push(i);
+        }
+    }
+
+    let entries: Vec<Value> = regenerated.iter().map(serde_json::to_value).collect::<Result<_, _>>()?;
+    let taken: HashSet<u64> = entries.iter().filter_map(|entry| entry["index"].as_u64()).collect();
+
+    let mut touched = vec!["index.json".to_owned()];
+    for entry in entries {
+        let same_index = entry["index"]
+            .as_u64()
+            .and_then(|idx| by_index.get(&idx))
+            .filter(|&&position| index[position]["name"] == entry["name"]);
+        let same_name = match entry["name"].as_str().and_then(|name| by_name.get(name)) {
+            Some(positions) if positions.len() == 1 => Some(&positions[0]),
+            _ => None,
+        };
+        match (same_index, same_name) {
+            (Some(&position), _) => index[position] = entry,
+            (None, Some(&position)) => {
+                if let Some(old) = index[position]["index"].as_u64() {
+                    if !taken.contains(&old) {
+                        touched.extend(remove_definition_files(output, old)?);
+                    }
+                }
+                index[position] = entry;
+            }
+            (None, None) => index.push(entry),
+        }
+    }
+    std::fs::write(path, serde_json::to_string(&index)?)?;
+    Ok(touched)
+}
+
+fn remove_definition_files(output: &Path, index: u64) -> anyhow::Result<Vec<String>> {
+    let file = format!("{}.json", index);
+    let mut removed = vec![];
+    let names = PER_DEFINITION_DIRS
+        .iter()
+        .map(|dir| format!("{}/{}", dir, file))
+        .chain(std::iter::once(file.clone()));
+    for name in names {
+        if output.join(&name).exists() {
+            std::fs::remove_file(output.join(&name))?;
+            removed.push(name);
+        }
+    }
+    Ok(removed)
+}
@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use anyhow::{anyhow, bail};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde_json::json;
+
+use crate::diff::read_json;
+use crate::manifest::{MANIFEST_FILE, SIGNATURE_FILE};
+
+/// Signs the manifest of an output directory with an ed25519 key, writing the signature along
+/// with the public key next to it.
+///
+/// The key file holds the 32-byte secret key in hex.
+pub fn sign_manifest(output: &Path, key_path: &Path) -> anyhow::Result<()> {
+    let key = SigningKey::from_bytes(&read_key(key_path)?);
+    let manifest = std::fs::read(output.join(MANIFEST_FILE))?;
+    let signature = key.sign(&manifest);
+    let signed = json!({
+        "algorithm": "ed25519",
+        "publicKey": hex::encode(key.verifying_key().to_bytes()),
+        "signature": hex::encode(signature.to_bytes()),
+    });
+    std::fs::write(output.join(SIGNATURE_FILE), serde_json::to_string_pretty(&signed)?)?;
+    Ok(())
+}
+
+/// Checks that the manifest of an output directory was signed by the holder of a public key.
+///
+/// The key file holds the 32-byte public key in hex.
+pub fn verify_manifest(output: &Path, key_path: &Path) -> anyhow::Result<()> {
+    let key = VerifyingKey::from_bytes(&read_key(key_path)?)?;
+    let signed = read_json(&output.join(SIGNATURE_FILE))?;
+    if signed["publicKey"].as_str() != Some(hex::encode(key.to_bytes()).as_str()) {
+        bail!("the manifest was signed with a different key");
+    }
+    let signature = signed["signature"]
+        .as_str()
+        .ok_or_else(|| anyhow!("{} has no signature", SIGNATURE_FILE))?;
+    let signature: [u8; 64] = hex::decode(signature)?
+        .try_into()
+        .map_err(|_| anyhow!("the signature is not 64 bytes long"))?;
+    let manifest = std::fs::read(output.join(MANIFEST_FILE))?;
+    key.verify(&manifest, &Signature::from_bytes(&signature))
+        .map_err(|_| anyhow!("the manifest signature is invalid"))
+}
+
+fn read_key(path: &Path) -> anyhow::Result<[u8; 32]> {
+    hex::decode(std::fs::read_to_string(path)?.trim())?
+        .try_into()
+        .map_err(|_| anyhow!("key in {} is not 32 bytes long", path.display()))
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use redscript::definition::{AnyDefinition, Definition};
+use serde_json::{json, Value};
+
+use crate::history::Churn;
+
+const CHURN_WEIGHT: f64 = 0.5;
+const NATIVE_WEIGHT: f64 = 0.2;
+const FINAL_WEIGHT: f64 = 0.1;
+const USAGE_WEIGHT: f64 = 0.2;
+/// Number of usages from which a symbol counts as fully established.
+const ESTABLISHED_USAGES: f64 = 100.0;
+
+/// Rates how safe root definitions are to build on from 0 to 100, combining how often they
+/// changed across the recorded history, whether they are native or final and how much the
+/// scripts themselves depend on them.
+///
+/// Factors that don't apply, like churn without a history or flags of enums, are left out and
+/// the remaining ones are weighted proportionally.
+#[derive(Default)]
+pub struct Stability {
+    versions: u32,
+    changes: HashMap<String, usize>,
+}
+
+impl Stability {
+    pub fn new(churn: Option<&Churn>) -> Self {
+        match churn {
+            Some(churn) => Self {
+                versions: churn.versions(),
+                changes: churn
+                    .changes()
+                    .map(|(name, versions)| (name.to_owned(), versions))
+                    .collect(),
+            },
+            None => Self::default(),
+        }
+    }
+
+    pub fn assess(&self, name: &str, def: &Definition, usage_count: u32) -> Assessment {
+        let churn = (self.versions > 0).then(|| {
+            let changed = self.changes.get(name).copied().unwrap_or(0) as f64;
+            1.0 - (changed / f64::from(self.versions)).min(1.0)
+        });
+        let (native, final_) = match &def.value {
+            AnyDefinition::Class(class) => (Some(class.flags.is_native()), Some(class.flags.is_final())),
+            AnyDefinition::Function(fun) => (Some(fun.flags.is_native()), Some(fun.flags.is_final())),
+            _ => (None, None),
+        };
+        let usage = ((1.0 + f64::from(usage_count)).log10() / (1.0 + ESTABLISHED_USAGES).log10()).min(1.0);
+
+        let factors = [
+            (churn, CHURN_WEIGHT),
+            (native.map(f64::from), NATIVE_WEIGHT),
+            (final_.map(f64::from), FINAL_WEIGHT),
+            (Some(usage), USAGE_WEIGHT),
+        ];
+        let (sum, weights) = factors
+            .iter()
+            .filter_map(|(factor, weight)| Some((factor.as_ref()? * weight, weight)))
+            .fold((0.0, 0.0), |(sum, weights), (value, weight)| {
+                (sum + value, weights + weight)
+            });
+        Assessment {
+            score: (100.0 * sum / weights).round() as u8,
+            churn: churn.map(|_| self.changes.get(name).copied().unwrap_or(0)),
+            native,
+            final_,
+            usage_count,
+        }
+    }
+}
+
+pub struct Assessment {
+    pub score: u8,
+    churn: Option<usize>,
+    native: Option<bool>,
+    final_: Option<bool>,
+    usage_count: u32,
+}
+
+impl Assessment {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "score": self.score,
+            "factors": {
+                "versionsChanged": self.churn,
+                "isNative": self.native,
+                "isFinal": self.final_,
+                "usageCount": self.usage_count,
+            }
+        })
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use redscript::bundle::{ConstantPool, PoolIndex};
+use redscript::definition::{AnyDefinition, Definition, Type};
+use serde_json::{json, Value};
+
+use crate::analysis::Analysis;
+use crate::recovery::Recovery;
+use crate::render::render_type;
+use crate::usage::type_chain;
+use crate::{encode_definition, encode_owned_ref};
+
+/// Lists every static array type along with its element type, size and the fields,
+/// parameters and functions using it. Types that fail to encode are left to the recovery.
+pub fn static_array_report(pool: &ConstantPool, analysis: &Analysis, recovery: &Recovery) -> anyhow::Result<Value> {
+    let mut arrays: BTreeMap<u32, (PoolIndex<Type>, Vec<PoolIndex<Definition>>)> = BTreeMap::new();
+    for (idx, def) in pool.definitions() {
+        for type_ in declared_types(def) {
+            let Some(arrays) = recovery.attempt(type_, static_arrays_in(type_, pool, analysis))? else {
+                continue;
+            };
+            for array in arrays {
+                arrays
+                    .entry(u32::from(array))
+                    .or_insert_with(|| (array, vec![]))
+                    .1
+                    .push(idx);
+            }
+        }
+    }
+
+    let mut report = vec![];
+    for (array, users) in arrays.into_values() {
+        let def = pool.definition(array)?;
+        let AnyDefinition::Type(Type::StaticArray(inner, size)) = &def.value else {
+            continue;
+        };
+        let rendered = || -> anyhow::Result<(String, String)> {
+            let type_ = render_type(&encode_definition(def, pool, analysis)?);
+            let element = render_type(&encode_definition(pool.definition(*inner)?, pool, analysis)?);
+            Ok((type_, element))
+        };
+        let Some((type_, element)) = recovery.attempt(array, rendered())? else {
+            continue;
+        };
+        let usages: anyhow::Result<Vec<Value>> = users.into_iter().map(|idx| encode_owned_ref(idx, pool)).collect();
+        report.push(json!({
+            "index": u32::from(array),
+            "type": type_,
+            "elementType": element,
+            "size": size,
+            "usages": usages?,
+        }));
+    }
+    Ok(Value::Array(report))
+}
+
+/// Counts how often every type is used by fields, parameters and function return types,
+/// ordered from the most used, along with the totals per kind of type. Types that fail to encode
+/// are left to the recovery.
+pub fn type_stats(pool: &ConstantPool, analysis: &Analysis, recovery: &Recovery) -> anyhow::Result<Value> {
+    let mut counts: BTreeMap<u32, (PoolIndex<Type>, [usize; 3])> = BTreeMap::new();
+    for (_, def) in pool.definitions() {
+        let position = match &def.value {
+            AnyDefinition::Field(_) => 0,
+            AnyDefinition::Parameter(_) => 1,
+            AnyDefinition::Function(_) => 2,
+            _ => continue,
+        };
+        for type_ in declared_types(def) {
+            counts.entry(u32::from(type_)).or_insert_with(|| (type_, [0; 3])).1[position] += 1;
+        }
+    }
+
+    let mut types = vec![];
+    let mut by_kind: BTreeMap<&str, usize> = BTreeMap::new();
+    for (type_, [fields, parameters, returns]) in counts.into_values() {
+        let def = pool.definition(type_)?;
+        let kind = match &def.value {
+            AnyDefinition::Type(Type::Prim) => "primitive",
+            AnyDefinition::Type(Type::Class) => "class",
+            AnyDefinition::Type(Type::Ref(_)) => "ref",
+            AnyDefinition::Type(Type::WeakRef(_)) => "weakRef",
+            AnyDefinition::Type(Type::ScriptRef(_)) => "scriptRef",
+            AnyDefinition::Type(Type::Array(_)) => "array",
+            AnyDefinition::Type(Type::StaticArray(_, _)) => "staticArray",
+            _ => continue,
+        };
+        let Some(encoded) = recovery.attempt(type_, encode_definition(def, pool, analysis))? else {
+            continue;
+        };
+        let total = fields + parameters + returns;
+        *by_kind.entry(kind).or_default() += total;
+        types.push(json!({
+            "index": u32::from(type_),
+            "type": render_type(&encoded),
+            "kind": kind,
+            "fields": fields,
+            "parameters": parameters,
+            "returns": returns,
+            "total": total,
+        }));
+    }
+    types.sort_by_key(|entry| std::cmp::Reverse(entry["total"].as_u64()));
+    Ok(json!({ "byKind": by_kind, "types": types }))
+}
+
+/// Returns the types a field, parameter or function signature is declared with.
+fn declared_types(def: &Definition) -> Vec<PoolIndex<Type>> {
+    match &def.value {
+        AnyDefinition::Field(field) => vec![field.type_],
+        AnyDefinition::Parameter(param) => vec![param.type_],
+        AnyDefinition::Function(fun) => fun.return_type.into_iter().collect(),
+        _ => vec![],
+    }
+}
+
+fn static_arrays_in(
+    idx: PoolIndex<Type>,
+    pool: &ConstantPool,
+    analysis: &Analysis,
+) -> anyhow::Result<Vec<PoolIndex<Type>>> {
+    let arrays = type_chain(idx, pool, analysis.max_type_depth())?
+        .into_iter()
+        .filter(|(_, def)| matches!(&def.value, AnyDefinition::Type(Type::StaticArray(..))))
+        .map(|(idx, _)| idx)
+        .collect();
+    Ok(arrays)
+}
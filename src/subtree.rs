@@ -0,0 +1,51 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::anyhow;
+use redscript::bundle::{ConstantPool, PoolIndex};
+use redscript::definition::{AnyDefinition, Class, Definition};
+
+use crate::dependencies::signature_types;
+use crate::usage::{resolve_type, type_lookup};
+
+/// Collects a class together with its ancestors, its descendants and every class or enum
+/// referenced by their fields and method signatures.
+pub fn collect_subtree(pool: &ConstantPool, root_name: &str) -> anyhow::Result<HashSet<PoolIndex<Definition>>> {
+    let mut children: HashMap<PoolIndex<Definition>, Vec<PoolIndex<Definition>>> = HashMap::new();
+    let mut root = None;
+    for (idx, def) in pool.definitions() {
+        if let AnyDefinition::Class(class) = &def.value {
+            if class.base != PoolIndex::UNDEFINED {
+                children.entry(class.base.cast()).or_default().push(idx);
+            }
+            if pool.names.get(def.name)?.as_ref() == root_name {
+                root = Some(idx);
+            }
+        }
+    }
+    let root = root.ok_or_else(|| anyhow!("class {} not found in the bundle", root_name))?;
+
+    let mut classes = HashSet::new();
+    let mut pending = vec![root];
+    while let Some(idx) = pending.pop() {
+        if classes.insert(idx) {
+            pending.extend(children.get(&idx).into_iter().flatten().copied());
+        }
+    }
+    // stops at the first repeated base, malformed bundles can have cyclic base chains
+    let mut bases = HashSet::new();
+    let mut base = pool.class(root.cast())?.base;
+    while base != PoolIndex::UNDEFINED && bases.insert(base) {
+        classes.insert(base.cast());
+        base = pool.class(base)?.base;
+    }
+
+    let types = type_lookup(pool);
+    let mut selected = classes.clone();
+    for idx in classes {
+        let class: &Class = pool.class(idx.cast())?;
+        for type_ in signature_types(class, pool)? {
+            selected.extend(resolve_type(type_, &types, pool)?);
+        }
+    }
+    Ok(selected)
+}
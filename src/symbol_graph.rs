@@ -0,0 +1,141 @@
+use redscript::bundle::{ConstantPool, PoolIndex};
+use redscript::definition::{AnyDefinition, Definition};
+use serde_json::{json, Value};
+
+use crate::analysis::Analysis;
+use crate::encode_definition;
+use crate::render::{class_header, function_signature, pretty_name, render_type};
+
+const LANGUAGE: &str = "redscript";
+
+/// Builds a symbol graph in the format consumed by DocC and similar renderers, with a symbol for
+/// every root definition and its members, and `memberOf`, `inheritsFrom` and `overrides`
+/// relationships between them.
+pub fn symbol_graph(
+    module: &str,
+    pool: &ConstantPool,
+    roots: &[(PoolIndex<Definition>, &Definition)],
+    analysis: &Analysis,
+) -> anyhow::Result<Value> {
+    let mut symbols = vec![];
+    let mut relationships = vec![];
+    for &(_, def) in roots {
+        let doc = encode_definition(def, pool, analysis)?;
+        let name = pool.names.get(def.name)?;
+        let id = precise_id(&name, None);
+        let path = vec![pretty_name(&doc["name"]).to_owned()];
+        match &def.value {
+            AnyDefinition::Class(class) => {
+                let kind = if class.flags.is_struct() { "struct" } else { "class" };
+                symbols.push(symbol(&id, kind, &path, &doc["visibility"], class_header(&doc)));
+                if class.base != PoolIndex::UNDEFINED {
+                    let base = pool.def_name(class.base)?;
+                    relationships.push(relationship("inheritsFrom", &id, &precise_id(&base, None)));
+                }
+                for (field, field_idx) in doc["fields"].as_array().into_iter().flatten().zip(&class.fields) {
+                    let member = member_path(&path, field);
+                    let field_id = precise_id(&name, field["name"].as_str());
+                    let visibility = format!("{}", pool.field(*field_idx)?.visibility).to_lowercase();
+                    let declaration = format!("let {}: {}", member[1], render_type(&field["type"]));
+                    symbols.push(symbol(&field_id, "property", &member, &visibility.into(), declaration));
+                    relationships.push(relationship("memberOf", &field_id, &id));
+                }
+                for (method, fun_idx) in doc["methods"].as_array().into_iter().flatten().zip(&class.functions) {
+                    let member = member_path(&path, method);
+                    let method_id = precise_id(&name, method["name"].as_str());
+                    let kind = if method["isStatic"] == true {
+                        "type.method"
+                    } else {
+                        "method"
+                    };
+                    symbols.push(symbol(
+                        &method_id,
+                        kind,
+                        &member,
+                        &method["visibility"],
+                        function_signature(method),
+                    ));
+                    relationships.push(relationship("memberOf", &method_id, &id));
+                    if let Some(base) = pool.function(*fun_idx)?.base_method {
+                        let base_def = pool.definition(base)?;
+                        let owner = pool.def_name(base_def.parent)?;
+                        let base_name = pool.names.get(base_def.name)?;
+                        relationships.push(relationship(
+                            "overrides",
+                            &method_id,
+                            &precise_id(&owner, Some(&base_name)),
+                        ));
+                    }
+                }
+            }
+            AnyDefinition::Enum(_) => {
+                let declaration = format!("enum {}", path[0]);
+                symbols.push(symbol(&id, "enum", &path, &"public".into(), declaration));
+                for member in doc["members"].as_array().into_iter().flatten() {
+                    let case = member_path(&path, member);
+                    let case_id = precise_id(&name, member["name"].as_str());
+                    let declaration = format!("{} = {}", case[1], member["value"]);
+                    symbols.push(symbol(&case_id, "enum.case", &case, &"public".into(), declaration));
+                    relationships.push(relationship("memberOf", &case_id, &id));
+                }
+            }
+            AnyDefinition::Function(_) => {
+                symbols.push(symbol(&id, "func", &path, &doc["visibility"], function_signature(&doc)));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(json!({
+        "metadata": {
+            "formatVersion": {"major": 0, "minor": 6, "patch": 0},
+            "generator": format!("cyberdoc-generator {}", env!("CARGO_PKG_VERSION")),
+        },
+        "module": {"name": module, "platform": {}},
+        "symbols": symbols,
+        "relationships": relationships,
+    }))
+}
+
+/// Identifies symbols by name rather than pool index, so that links stay valid across versions.
+fn precise_id(owner: &str, member: Option<&str>) -> String {
+    match member {
+        Some(member) => format!("reds:{}::{}", owner, member),
+        None => format!("reds:{}", owner),
+    }
+}
+
+fn member_path(owner: &[String], member: &Value) -> Vec<String> {
+    let mut path = owner.to_vec();
+    path.push(pretty_name(&member["name"]).to_owned());
+    path
+}
+
+fn symbol(id: &str, kind: &str, path: &[String], visibility: &Value, declaration: String) -> Value {
+    let title = path.last().cloned().unwrap_or_default();
+    json!({
+        "identifier": {"precise": id, "interfaceLanguage": LANGUAGE},
+        "kind": {"identifier": format!("{}.{}", LANGUAGE, kind), "displayName": display_name(kind)},
+        "pathComponents": path,
+        "names": {"title": title},
+        "accessLevel": visibility.as_str().unwrap_or("public"),
+        "declarationFragments": [{"kind": "text", "spelling": declaration}],
+    })
+}
+
+fn display_name(kind: &str) -> &'static str {
+    match kind {
+        "struct" => "Structure",
+        "class" => "Class",
+        "property" => "Instance Property",
+        "method" => "Instance Method",
+        "type.method" => "Type Method",
+        "enum" => "Enumeration",
+        "enum.case" => "Case",
+        _ => "Function",
+    }
+}
+
+fn relationship(kind: &str, source: &str, target: &str) -> Value {
+    json!({"kind": kind, "source": source, "target": target})
+}
@@ -0,0 +1,214 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::Arc;
+
+use redscript::bundle::{ConstantPool, PoolIndex};
+use redscript::bytecode::Instr;
+use redscript::definition::{AnyDefinition, Class, Definition};
+use serde_json::{json, Value};
+
+use crate::instantiation::{derives_from, EVENT_BASES};
+use crate::usage::{resolve_type, type_lookup};
+
+const SYSTEM_SUFFIX: &str = "System";
+const REQUEST_BASES: &[&str] = &["ScriptableSystemRequest"];
+/// Shortest name prefix events are matched to a system by, shorter ones match too broadly.
+const MIN_PREFIX_LEN: usize = 3;
+
+/// Connects the major subsystems of the game into one overview: every class named like a system
+/// along with the events and requests it handles or shares its name with, the classes its fields
+/// hold and the classes whose code calls into it.
+pub fn system_map(pool: &ConstantPool, roots: &[(PoolIndex<Definition>, &Definition)]) -> anyhow::Result<SystemMap> {
+    let types = type_lookup(pool);
+    let mut events: BTreeMap<String, PoolIndex<Definition>> = BTreeMap::new();
+    let mut systems: Vec<(PoolIndex<Definition>, &Class)> = vec![];
+    for &(idx, def) in roots {
+        let AnyDefinition::Class(class) = &def.value else {
+            continue;
+        };
+        if derives_from(class, EVENT_BASES, pool)? || derives_from(class, REQUEST_BASES, pool)? {
+            events.insert(pool.names.get(def.name)?.to_string(), idx);
+        } else if pool.names.get(def.name)?.ends_with(SYSTEM_SUFFIX) {
+            systems.push((idx, class));
+        }
+    }
+
+    let event_set: HashSet<PoolIndex<Definition>> = events.values().copied().collect();
+
+    // owners of the functions calling each method, to attribute calls to the classes making them,
+    // virtual calls only carry the method name and are resolved against the methods of each system
+    let mut callers: HashMap<PoolIndex<Definition>, BTreeSet<PoolIndex<Definition>>> = HashMap::new();
+    let mut virtual_callers: HashMap<Arc<str>, BTreeSet<PoolIndex<Definition>>> = HashMap::new();
+    // classes defining a method of each name and the subclasses of each class
+    let mut definers: HashMap<Arc<str>, HashSet<PoolIndex<Definition>>> = HashMap::new();
+    let mut children: HashMap<PoolIndex<Definition>, Vec<PoolIndex<Definition>>> = HashMap::new();
+    for (idx, def) in pool.definitions() {
+        let fun = match &def.value {
+            AnyDefinition::Function(fun) => fun,
+            AnyDefinition::Class(class) => {
+                if class.base != PoolIndex::UNDEFINED {
+                    children.entry(class.base.cast()).or_default().push(idx);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        if def.parent != PoolIndex::UNDEFINED {
+            definers
+                .entry(pool.names.get(def.name)?)
+                .or_default()
+                .insert(def.parent);
+        }
+        for instr in &fun.code.0 {
+            match instr {
+                Instr::InvokeStatic(_, _, callee, ..) => {
+                    let callee = pool.definition(*callee)?;
+                    if callee.parent != PoolIndex::UNDEFINED && def.parent != callee.parent {
+                        callers.entry(callee.parent).or_default().insert(def.parent);
+                    }
+                }
+                Instr::InvokeVirtual(_, _, name, ..) => {
+                    virtual_callers
+                        .entry(pool.names.get(*name)?)
+                        .or_default()
+                        .insert(def.parent);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut entries = vec![];
+    for (idx, class) in systems {
+        let name = pool.def_name(idx)?;
+        let mut handled = BTreeSet::new();
+        for fun in &class.functions {
+            let fun = pool.function(*fun)?;
+            for param in &fun.parameters {
+                let param = pool.parameter(*param)?;
+                if let Some(type_) = resolve_type(param.type_, &types, pool)? {
+                    if event_set.contains(&type_) {
+                        handled.insert(type_);
+                    }
+                }
+            }
+        }
+        let prefix = name.strip_suffix(SYSTEM_SUFFIX).unwrap_or_default();
+        if prefix.len() >= MIN_PREFIX_LEN {
+            handled.extend(
+                events
+                    .range(prefix.to_owned()..)
+                    .take_while(|(event, _)| event.starts_with(prefix))
+                    .map(|(_, idx)| *idx),
+            );
+        }
+
+        let mut owned = BTreeSet::new();
+        for field in &class.fields {
+            if let Some(type_) = resolve_type(pool.field(*field)?.type_, &types, pool)? {
+                if type_ != idx && matches!(pool.definition(type_)?.value, AnyDefinition::Class(_)) {
+                    owned.insert(type_);
+                }
+            }
+        }
+
+        // a virtual call is only attributed to the system when no class outside of its hierarchy
+        // has a method of that name, common names like `OnAttach` would match everything otherwise
+        let hierarchy = class_hierarchy(idx, &children, pool)?;
+        let mut called_from: BTreeSet<PoolIndex<Definition>> = callers.get(&idx).cloned().unwrap_or_default();
+        for fun in &class.functions {
+            let name = pool.names.get(pool.definition(*fun)?.name)?;
+            let unique = definers
+                .get(&name)
+                .is_some_and(|definers| definers.iter().all(|definer| hierarchy.contains(definer)));
+            if unique {
+                called_from.extend(virtual_callers.get(&name).into_iter().flatten());
+            }
+        }
+        let called_from = called_from
+            .iter()
+            .filter(|caller| **caller != PoolIndex::UNDEFINED && **caller != idx);
+        let base = if class.base == PoolIndex::UNDEFINED {
+            None
+        } else {
+            Some(pool.def_name(class.base)?.to_string())
+        };
+        entries.push(json!({
+            "name": name.as_ref(),
+            "index": u32::from(idx),
+            "base": base,
+            "events": references(handled.iter(), pool)?,
+            "owns": references(owned.iter(), pool)?,
+            "calledFrom": references(called_from, pool)?,
+        }));
+    }
+    entries.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+    Ok(SystemMap { entries })
+}
+
+pub struct SystemMap {
+    entries: Vec<Value>,
+}
+
+impl SystemMap {
+    pub fn to_json(&self) -> Value {
+        Value::Array(self.entries.clone())
+    }
+
+    pub fn render_markdown(&self) -> anyhow::Result<String> {
+        let mut out = String::from("# System map\n");
+        writeln!(
+            out,
+            "\n{} systems with the events they handle, the classes they hold and the classes calling them.",
+            self.entries.len()
+        )?;
+        for entry in &self.entries {
+            writeln!(out, "\n## {}\n", entry["name"].as_str().unwrap_or_default())?;
+            if let Some(base) = entry["base"].as_str() {
+                writeln!(out, "Extends `{}`.\n", base)?;
+            }
+            for (key, title) in [("events", "Events"), ("owns", "Owns"), ("calledFrom", "Called from")] {
+                let names: Vec<String> = entry[key]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|reference| Some(format!("`{}`", reference["name"].as_str()?)))
+                    .collect();
+                if !names.is_empty() {
+                    writeln!(out, "- {}: {}", title, names.join(", "))?;
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Collects a class along with its bases and its subclasses, stopping at repeated classes of
+/// cyclic chains.
+fn class_hierarchy(
+    idx: PoolIndex<Definition>,
+    children: &HashMap<PoolIndex<Definition>, Vec<PoolIndex<Definition>>>,
+    pool: &ConstantPool,
+) -> anyhow::Result<HashSet<PoolIndex<Definition>>> {
+    let mut hierarchy = HashSet::new();
+    let mut pending = vec![idx];
+    while let Some(next) = pending.pop() {
+        if hierarchy.insert(next) {
+            pending.extend(children.get(&next).into_iter().flatten().copied());
+        }
+    }
+    let mut base = pool.class(idx.cast())?.base;
+    while base != PoolIndex::UNDEFINED && hierarchy.insert(base.cast()) {
+        base = pool.class(base)?.base;
+    }
+    Ok(hierarchy)
+}
+
+fn references<'a>(
+    indices: impl Iterator<Item = &'a PoolIndex<Definition>>,
+    pool: &ConstantPool,
+) -> anyhow::Result<Vec<Value>> {
+    indices
+        .map(|idx| Ok(json!({"name": pool.def_name(*idx)?.as_ref(), "index": u32::from(*idx)})))
+        .collect()
+}
@@ -0,0 +1,394 @@
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use redscript::bundle::{PoolIndex, ScriptBundle};
+use redscript::definition::AnyDefinition;
+use serde_json::{json, Value};
+
+use crate::analysis::{self, Analysis};
+use crate::diff::{read_json, Diff, Snapshot};
+use crate::document::Documents;
+use crate::enums::{self, EnumOrder};
+use crate::fixture::{cyclic_pool, synthetic_pool};
+use crate::ignore::IgnoreList;
+use crate::recovery::Recovery;
+use crate::{build_index, build_types, encode_definition, is_documented, lint, render, scoped, stats, Reference};
+
+/// Enough classes for an override chain and a struct, see `synthetic_pool`.
+const FIXTURE_CLASSES: usize = 4;
+
+/// Builds the fixture and passes it through a save and load like a real bundle would be.
+fn fixture_bundle() -> ScriptBundle {
+    let bundle = ScriptBundle {
+        pool: synthetic_pool(FIXTURE_CLASSES),
+    };
+    let mut bytes = Cursor::new(vec![]);
+    bundle.save(&mut bytes).unwrap();
+    bytes.set_position(0);
+    ScriptBundle::load(&mut bytes).unwrap()
+}
+
+/// Compares `actual` with the snapshot stored under `tests/golden`. Setting `UPDATE_GOLDEN`
+/// writes the snapshots instead, after an intended change or when adding a new one.
+fn assert_golden(name: &str, actual: &Value) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden", &format!("{}.json", name)]
+        .iter()
+        .collect();
+    let actual = serde_json::to_string_pretty(actual).unwrap() + "\n";
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let Ok(expected) = std::fs::read_to_string(&path) else {
+        panic!("{} is missing, run with UPDATE_GOLDEN=1 to create it", path.display());
+    };
+    assert!(
+        expected == actual,
+        "{} does not match the generated output, rerun with UPDATE_GOLDEN=1 if the change is intended\n{}",
+        path.display(),
+        actual
+    );
+}
+
+/// Creates an empty directory for a test to write an output directory to.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cyberdoc-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Writes documents along with an index listing them, like generated output directories have.
+fn write_output(dir: &Path, docs: &[(u32, Value)]) {
+    let index: Vec<Value> = docs
+        .iter()
+        .map(|(idx, doc)| json!({"name": render::unmangled(doc["name"].as_str().unwrap()), "index": idx}))
+        .collect();
+    std::fs::write(dir.join("index.json"), serde_json::to_string(&index).unwrap()).unwrap();
+    for (idx, doc) in docs {
+        std::fs::write(dir.join(format!("{}.json", idx)), serde_json::to_string(doc).unwrap()).unwrap();
+    }
+}
+
+#[test]
+fn documents_match_golden() {
+    let bundle = fixture_bundle();
+    let pool = &bundle.pool;
+    let analysis = Analysis::build(pool).unwrap();
+    let documents = Documents::new(pool, &analysis).unwrap();
+    for (idx, def) in pool.roots().filter(|(_, def)| is_documented(def)) {
+        let name = pool.names.get(def.name).unwrap();
+        let doc = documents.encode(idx, def).unwrap();
+        assert_golden(&format!("definitions/{}", name.replace(';', "_")), &doc);
+    }
+}
+
+#[test]
+fn index_and_type_reports_match_golden() {
+    let bundle = fixture_bundle();
+    let pool = &bundle.pool;
+    let analysis = Analysis::build(pool).unwrap();
+    let documents = Documents::new(pool, &analysis).unwrap();
+    let roots: Vec<_> = pool.roots().filter(|(_, def)| is_documented(def)).collect();
+    let index = build_index(pool, &roots, &analysis.usages, None, Some(&documents.stability), true);
+    assert_golden("index", &serde_json::to_value(index).unwrap());
+    assert_golden(
+        "types",
+        &serde_json::to_value(build_types(pool, &analysis, &Recovery::strict()).unwrap()).unwrap(),
+    );
+    assert_golden(
+        "type-stats",
+        &stats::type_stats(pool, &analysis, &Recovery::strict()).unwrap(),
+    );
+}
+
+#[test]
+fn fixture_has_no_warnings() {
+    let bundle = fixture_bundle();
+    assert_eq!(lint::collect_warnings(&bundle.pool).unwrap(), Vec::<Value>::new());
+}
+
+#[test]
+fn fixture_covers_edge_cases() {
+    let bundle = fixture_bundle();
+    let pool = &bundle.pool;
+    let analysis = Analysis::build(pool).unwrap();
+    let docs: Vec<Value> = pool
+        .roots()
+        .filter(|(_, def)| matches!(&def.value, AnyDefinition::Class(_)))
+        .map(|(_, def)| encode_definition(def, pool, &analysis).unwrap())
+        .collect();
+
+    let has = |key: &str| docs.iter().any(|doc| doc[key] == true);
+    assert!(has("isStruct") && has("isAbstract") && has("isNative") && has("isFinal"));
+
+    let fields = docs.iter().flat_map(|doc| doc["fields"].as_array().unwrap());
+    for key in ["isNative", "isEdit", "isInline", "isConst", "isRep", "isPersistent"] {
+        assert!(fields.clone().any(|field| field[key] == true), "no field with {}", key);
+    }
+    let parameters: Vec<&Value> = docs
+        .iter()
+        .flat_map(|doc| doc["methods"].as_array().unwrap())
+        .flat_map(|method| method["parameters"].as_array().unwrap())
+        .collect();
+    assert!(parameters.iter().any(|param| param["isOut"] == true));
+    assert!(parameters
+        .iter()
+        .any(|param| param["isOptional"] == true && param["defaultValue"] == "false"));
+}
+
+#[test]
+fn cyclic_types_fail_without_overflowing() {
+    let pool = cyclic_pool();
+    let analysis = Analysis::build(&pool).unwrap();
+    let (_, class) = pool
+        .roots()
+        .find(|(_, def)| matches!(&def.value, AnyDefinition::Class(_)))
+        .unwrap();
+    assert!(encode_definition(class, &pool, &analysis).is_err());
+    assert!(stats::static_array_report(&pool, &analysis, &Recovery::strict()).is_err());
+
+    let recovery = Recovery::new(true);
+    assert!(stats::static_array_report(&pool, &analysis, &recovery).is_ok());
+    assert!(build_types(&pool, &analysis, &recovery).is_ok());
+    assert!(!recovery.into_skipped().is_empty());
+
+    let warnings = lint::collect_warnings(&pool).unwrap();
+    assert!(warnings.iter().any(|warning| warning["kind"] == "undefinedReturnType"));
+}
+
+#[test]
+fn malformed_definitions_fail_without_overflowing() {
+    let pool = cyclic_pool();
+    let analysis = Analysis::build(&pool).unwrap();
+    let named = |name: &str| {
+        pool.definitions()
+            .find(|(_, def)| pool.names.get(def.name).unwrap().as_ref() == name)
+            .unwrap()
+            .1
+    };
+    assert!(encode_definition(named("GetSelf;"), &pool, &analysis).is_err());
+
+    let doc = encode_definition(named("CycleFirst"), &pool, &analysis).unwrap();
+    let bases: Vec<&str> = doc["bases"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|base| base["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(bases, ["CycleSecond", "CycleFirst"]);
+}
+
+#[test]
+fn types_render_in_redscript_syntax() {
+    let bundle = fixture_bundle();
+    let pool = &bundle.pool;
+    let analysis = Analysis::build(pool).unwrap();
+    let (_, class) = pool
+        .roots()
+        .find(|(_, def)| pool.names.get(def.name).unwrap().as_ref() == "FixtureClass1")
+        .unwrap();
+    let doc = encode_definition(class, pool, &analysis).unwrap();
+    let field_type = |name: &str| {
+        let fields = doc["fields"].as_array().unwrap();
+        let field = fields.iter().find(|field| field["name"] == name).unwrap();
+        render::render_type(&field["type"])
+    };
+    assert_eq!(field_type("items"), "array<Int32>");
+    assert_eq!(field_type("fixed"), "[Int32; 4]");
+    assert_eq!(field_type("next"), "ref<FixtureClass1>");
+}
+
+#[test]
+fn documents_carry_generated_annotations() {
+    let bundle = fixture_bundle();
+    let pool = &bundle.pool;
+    let analysis = Analysis::build(pool).unwrap();
+    let documents = Documents::new(pool, &analysis).unwrap();
+    for (idx, def) in pool.roots().filter(|(_, def)| is_documented(def)) {
+        let doc = documents.encode(idx, def).unwrap();
+        for key in ["usageCount", "stability", "summaryHint"] {
+            assert!(!doc[key].is_null(), "{} is missing {}", doc["name"], key);
+        }
+        if doc["tag"] == "Class" {
+            assert!(!doc["dependencies"].is_null() && !doc["instantiation"].is_null());
+        }
+    }
+}
+
+#[test]
+fn declarations_are_not_stubs() {
+    let bundle = fixture_bundle();
+    let declarations: Vec<_> = bundle
+        .pool
+        .definitions()
+        .filter_map(|(_, def)| match &def.value {
+            AnyDefinition::Function(fun) if analysis::is_declaration(fun) => Some(fun),
+            _ => None,
+        })
+        .collect();
+    assert!(!declarations.is_empty());
+    assert!(declarations.iter().all(|fun| !analysis::is_stub(fun)));
+}
+
+#[test]
+fn arranged_enums_compare_equal() {
+    let doc = json!({
+        "tag": "Enum",
+        "members": [
+            {"name": "None", "value": 0},
+            {"name": "Fast", "value": 1},
+            {"name": "Default", "value": 0},
+        ]
+    });
+    let mut canonical = doc.clone();
+    enums::canonical_members(&mut canonical);
+    let mut arranged = doc;
+    enums::arrange_members(&mut arranged, EnumOrder::Value, true);
+    assert_eq!(arranged["members"].as_array().unwrap().len(), 2);
+    enums::canonical_members(&mut arranged);
+    assert_eq!(arranged, canonical);
+}
+
+#[test]
+fn ignore_rules_take_the_last_match() {
+    let ignore = IgnoreList::parse("# debug helpers\n\nDebug*\n!DebugDrawSystem\n*Test?\nLog\n");
+    assert_eq!(ignore.verdict("DebugUtils"), Some(false));
+    assert_eq!(ignore.verdict("DebugDrawSystem"), Some(true));
+    assert_eq!(ignore.verdict("GameTestA"), Some(false));
+    assert_eq!(ignore.verdict("GameTest"), None);
+    assert_eq!(ignore.verdict("Log;String"), Some(false));
+    assert_eq!(ignore.verdict("PlayerSystem"), None);
+
+    let ignore = IgnoreList::parse("!Debug*\nDebug*\n");
+    assert_eq!(ignore.verdict("DebugUtils"), Some(false));
+
+    let ignore = IgnoreList::parse("*\n!Keep*\n!Log**Entry\n");
+    assert_eq!(ignore.verdict(""), Some(false));
+    assert_eq!(ignore.verdict("Other"), Some(false));
+    assert_eq!(ignore.verdict("Keep"), Some(true));
+    assert_eq!(ignore.verdict("LogEntry"), Some(true));
+    assert_eq!(ignore.verdict("LogAnEntry"), Some(true));
+    assert_eq!(ignore.verdict("LogEntries"), Some(false));
+}
+
+fn function_doc(name: &str, parameters: &[&str]) -> Value {
+    let parameters: Vec<Value> = parameters
+        .iter()
+        .map(|type_| json!({"tag": "Parameter", "type": {"tag": "Type", "kind": "Prim", "name": type_}}))
+        .collect();
+    json!({"tag": "Function", "name": name, "parameters": parameters, "returnType": null})
+}
+
+#[test]
+fn overloads_with_changed_parameters_are_reported_as_changed() {
+    let owner =
+        |method: Value| json!({"tag": "Class", "name": "Owner", "bases": [], "fields": [], "methods": [method]});
+    let previous_dir = scratch_dir("diff-previous");
+    write_output(
+        &previous_dir,
+        &[
+            (1, function_doc("Foo;Int32", &["Int32"])),
+            (2, owner(function_doc("Bar;Int32", &["Int32"]))),
+        ],
+    );
+    let current_dir = scratch_dir("diff-current");
+    write_output(
+        &current_dir,
+        &[
+            (3, function_doc("Foo;Int32Bool", &["Int32", "Bool"])),
+            (2, owner(function_doc("Bar;Bool", &["Bool"]))),
+        ],
+    );
+    let previous = Snapshot::load(&previous_dir).unwrap();
+    let current = Snapshot::load(&current_dir).unwrap();
+
+    let changes = Diff::compute(&previous, &current).to_json();
+    assert_eq!(changes["added"], json!([]));
+    assert_eq!(changes["removed"], json!([]));
+    assert_eq!(
+        changes["changed"],
+        json!([{"name": "Foo;Int32Bool", "tag": "Function", "before": "(Int32)", "after": "(Int32, Bool)"}])
+    );
+    assert_eq!(
+        changes["members"]["Owner"],
+        json!({
+            "added": [],
+            "removed": [],
+            "changed": [{"name": "Bar;Bool", "tag": "Function", "before": "(Int32)", "after": "(Bool)"}],
+        })
+    );
+
+    let mut old = function_doc("Foo;Int32", &["Int32"]);
+    current.annotate_deprecated(&mut old);
+    assert!(old["deprecated"].is_null());
+    let mut old = owner(function_doc("Bar;Int32", &["Int32"]));
+    current.annotate_deprecated(&mut old);
+    assert!(old["methods"][0]["deprecated"].is_null());
+    let mut gone = function_doc("Gone;", &[]);
+    current.annotate_deprecated(&mut gone);
+    assert!(!gone["deprecated"].is_null());
+}
+
+fn reference(name: &str, index: u32) -> Reference {
+    Reference {
+        name: Arc::from(name),
+        index: PoolIndex::new(index),
+        base: None,
+        usage_count: None,
+        stable_id: None,
+        stability_score: None,
+        member_counts: None,
+        function_stats: None,
+    }
+}
+
+#[test]
+fn reshuffled_index_entries_are_patched_by_name() {
+    let dir = scratch_dir("patch-index");
+    write_output(
+        &dir,
+        &[
+            (1, json!({"name": "Foo"})),
+            (2, json!({"name": "Bar"})),
+            (3, json!({"name": "Baz"})),
+        ],
+    );
+    // a recompile swapped Foo and Bar and the pipeline already wrote their new documents
+    let touched = scoped::patch_index(&dir, &[reference("Foo", 2), reference("Bar", 1)]).unwrap();
+    assert_eq!(touched, ["index.json"]);
+
+    let index = read_json(&dir.join("index.json")).unwrap();
+    let entries: Vec<(&str, u64)> = index
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| (entry["name"].as_str().unwrap(), entry["index"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(entries, [("Foo", 2), ("Bar", 1), ("Baz", 3)]);
+    assert!(dir.join("1.json").exists() && dir.join("2.json").exists());
+}
+
+#[test]
+fn stale_index_entries_require_full_regeneration() {
+    let bundle = fixture_bundle();
+    let pool = &bundle.pool;
+    let analysis = Analysis::build(pool).unwrap();
+    let roots: Vec<_> = pool.roots().filter(|(_, def)| is_documented(def)).collect();
+    let dir = scratch_dir("check-index");
+    let index = build_index(pool, &roots, &analysis.usages, None, None, false);
+    std::fs::write(dir.join("index.json"), serde_json::to_string(&index).unwrap()).unwrap();
+    assert!(scoped::check_index(&dir, pool, &[]).is_ok());
+
+    let mut shuffled = serde_json::to_value(&index).unwrap();
+    let entries = shuffled.as_array_mut().unwrap();
+    let first = entries[0]["index"].clone();
+    entries[0]["index"] = entries[1]["index"].clone();
+    entries[1]["index"] = first;
+    std::fs::write(dir.join("index.json"), serde_json::to_string(&shuffled).unwrap()).unwrap();
+    let err = scoped::check_index(&dir, pool, &[]).unwrap_err();
+    assert!(err.to_string().contains("full regeneration required"));
+    assert!(scoped::check_index(&dir, pool, &roots[..2]).is_ok());
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+use redscript::bundle::{CName, ConstantPool, PoolIndex};
+use redscript::bytecode::Instr;
+use redscript::definition::{AnyDefinition, Definition, Type};
+use serde_json::{json, Value};
+
+use crate::analysis::Analysis;
+
+/// Counts how often each definition is referenced across the bundle.
+///
+/// Types are counted through field, parameter and return type signatures, functions through static
+/// calls. Virtual calls only carry a name, so they are attributed to every method with that name.
+pub struct UsageIndex {
+    counts: HashMap<PoolIndex<Definition>, u32>,
+}
+
+impl UsageIndex {
+    pub fn build(pool: &ConstantPool) -> anyhow::Result<Self> {
+        let types = type_lookup(pool);
+        let mut methods: HashMap<PoolIndex<CName>, Vec<PoolIndex<Definition>>> = HashMap::new();
+        for (idx, def) in pool.definitions() {
+            if let AnyDefinition::Function(_) = &def.value {
+                methods.entry(def.name).or_default().push(idx);
+            }
+        }
+
+        let mut counts: HashMap<PoolIndex<Definition>, u32> = HashMap::new();
+        let mut add = |idx: Option<PoolIndex<Definition>>| {
+            if let Some(idx) = idx {
+                *counts.entry(idx).or_default() += 1;
+            }
+        };
+        // broken types are reported when the definitions using them are encoded, they only
+        // don't count as usages here
+        let resolve = |type_| resolve_type(type_, &types, pool).ok().flatten();
+        for (_, def) in pool.definitions() {
+            match &def.value {
+                AnyDefinition::Field(field) => add(resolve(field.type_)),
+                AnyDefinition::Parameter(param) => add(resolve(param.type_)),
+                AnyDefinition::Function(fun) => {
+                    if let Some(ret) = fun.return_type {
+                        add(resolve(ret));
+                    }
+                    for instr in &fun.code.0 {
+                        match instr {
+                            Instr::InvokeStatic(_, _, callee, ..) => add(Some(callee.cast())),
+                            Instr::InvokeVirtual(_, _, name, ..) => {
+                                for callee in methods.get(name).into_iter().flatten() {
+                                    add(Some(*callee));
+                                }
+                            }
+                            Instr::New(class) | Instr::Construct(_, class) => add(Some(class.cast())),
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(Self { counts })
+    }
+
+    pub fn count<A>(&self, idx: PoolIndex<A>) -> u32 {
+        let idx: PoolIndex<Definition> = idx.cast();
+        self.counts.get(&idx).copied().unwrap_or(0)
+    }
+
+    /// Encodes the counts as `[index, count]` pairs.
+    pub fn to_json(&self) -> Value {
+        let counts: Vec<Value> = self
+            .counts
+            .iter()
+            .map(|(idx, count)| json!([u32::from(*idx), count]))
+            .collect();
+        Value::Array(counts)
+    }
+
+    pub fn from_json(value: &Value) -> Option<Self> {
+        let counts = value
+            .as_array()?
+            .iter()
+            .map(|pair| Some((PoolIndex::new(pair[0].as_u64()? as u32), pair[1].as_u64()? as u32)))
+            .collect::<Option<_>>()?;
+        Some(Self { counts })
+    }
+}
+
+/// Maps class and enum names to their definitions.
+pub fn type_lookup(pool: &ConstantPool) -> HashMap<PoolIndex<CName>, PoolIndex<Definition>> {
+    pool.definitions()
+        .filter(|(_, def)| matches!(&def.value, AnyDefinition::Class(_) | AnyDefinition::Enum(_)))
+        .map(|(idx, def)| (def.name, idx))
+        .collect()
+}
+
+/// Resolves a type to the class or enum it ultimately refers to, looking through references and arrays.
+pub fn resolve_type(
+    idx: PoolIndex<Type>,
+    types: &HashMap<PoolIndex<CName>, PoolIndex<Definition>>,
+    pool: &ConstantPool,
+) -> anyhow::Result<Option<PoolIndex<Definition>>> {
+    let chain = type_chain(idx, pool, Analysis::DEFAULT_MAX_TYPE_DEPTH)?;
+    match chain.last() {
+        Some((_, def)) if matches!(&def.value, AnyDefinition::Type(Type::Class)) => Ok(types.get(&def.name).copied()),
+        _ => Ok(None),
+    }
+}
+
+/// Follows a type through references and arrays, returning every type on the way from the
+/// outermost to the innermost one. Fails if the chain refers to itself or nests deeper than
+/// `max_depth`, like encoding does.
+pub fn type_chain(
+    idx: PoolIndex<Type>,
+    pool: &ConstantPool,
+    max_depth: usize,
+) -> anyhow::Result<Vec<(PoolIndex<Type>, &Definition)>> {
+    let mut chain: Vec<(PoolIndex<Type>, &Definition)> = vec![];
+    let mut next = Some(idx);
+    while let Some(idx) = next {
+        if chain.iter().any(|(visited, _)| *visited == idx) {
+            bail!("type {} refers to itself", u32::from(idx));
+        }
+        if chain.len() > max_depth {
+            bail!("type {} is nested deeper than {} levels", u32::from(idx), max_depth);
+        }
+        let def = pool.definition(idx)?;
+        next = match &def.value {
+            AnyDefinition::Type(Type::Prim | Type::Class) => None,
+            AnyDefinition::Type(
+                Type::Ref(inner)
+                | Type::WeakRef(inner)
+                | Type::ScriptRef(inner)
+                | Type::Array(inner)
+                | Type::StaticArray(inner, _),
+            ) => Some(*inner),
+            _ => bail!("definition {} is not a type", u32::from(idx)),
+        };
+        chain.push((idx, def));
+    }
+    Ok(chain)
+}
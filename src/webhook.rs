@@ -0,0 +1,62 @@
+use std::str::FromStr;
+
+use serde_json::{json, Value};
+
+use crate::diff::Diff;
+
+// Discord rejects messages longer than this
+const DISCORD_CONTENT_LIMIT: usize = 2000;
+
+/// Payload shape expected by the receiving end of a webhook.
+#[derive(Debug, Clone, Copy)]
+pub enum WebhookFormat {
+    Discord,
+    Slack,
+    Generic,
+}
+
+impl FromStr for WebhookFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "discord" => Ok(WebhookFormat::Discord),
+            "slack" => Ok(WebhookFormat::Slack),
+            "generic" => Ok(WebhookFormat::Generic),
+            other => Err(format!("unknown webhook format {}", other)),
+        }
+    }
+}
+
+/// Posts a summary of the API changes to a webhook.
+pub fn notify(url: &str, format: WebhookFormat, diff: &Diff, version: Option<&str>) -> anyhow::Result<()> {
+    let title = match version {
+        Some(version) => format!("API changes in {}", version),
+        None => "API changes".to_owned(),
+    };
+    let summary = diff.summary();
+    let payload = match format {
+        WebhookFormat::Discord => {
+            let content: String = format!("**{}**\n{}", title, summary)
+                .chars()
+                .take(DISCORD_CONTENT_LIMIT)
+                .collect();
+            json!({ "content": content })
+        }
+        WebhookFormat::Slack => json!({ "text": format!("*{}*\n{}", title, summary) }),
+        WebhookFormat::Generic => json!({
+            "title": title,
+            "version": version,
+            "summary": summary,
+            "changes": diff.to_json(),
+        }),
+    };
+    post(url, &payload)
+}
+
+fn post(url: &str, payload: &Value) -> anyhow::Result<()> {
+    ureq::post(url)
+        .set("Content-Type", "application/json")
+        .send_string(&serde_json::to_string(payload)?)?;
+    Ok(())
+}